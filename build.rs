@@ -0,0 +1,113 @@
+//! With the `static-data` feature, compiles the embedded dataset into fixed-width static tables,
+//! so that reading it needs neither bzip2 decompression nor JSON parsing at runtime.
+
+use bzip2::read::BzDecoder;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::env;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+/// Reads the enum declarations in `src/types/variants.rs` as tables of (variant, database string)
+/// pairs in declaration order, one module per enum.
+macro_rules! string_enum {
+    ($(#[$meta:meta])* pub enum $name:ident { $($(#[$variant_meta:meta])* $variant:ident => $value:literal,)* }) => {
+        #[allow(non_snake_case)]
+        mod $name {
+            pub const VARIANTS: &[(&str, &str)] = &[$((stringify!($variant), $value),)*];
+        }
+    };
+}
+
+include!("src/types/variants.rs");
+
+const DATASET: &str = "src/zips.json.bz2";
+
+// The layout of the tables, which `src/static_data.rs` reads. Each record holds the spans of its
+// string fields, then the spans of its list fields, then one byte each for `active` and the
+// positions of its `state`, `world_region` and `zip_code_type` in the enums' `ALL`. A span is a
+// little-endian u32 offset followed by a u16 length, into `strings.txt` for strings and into
+// `lists.bin`, itself a table of string spans, for lists.
+const STRING_FIELDS: &[&str] = &["zip_code", "city", "country", "county", "lat", "long", "timezone"];
+const LIST_FIELDS: &[&str] = &["area_codes", "acceptable_cities", "unacceptable_cities"];
+const SPAN_SIZE: usize = 6;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed={}", DATASET);
+    println!("cargo:rerun-if-changed=src/types/variants.rs");
+    if env::var_os("CARGO_FEATURE_STATIC_DATA").is_none() {
+        return;
+    }
+
+    let mut json = Vec::new();
+    BzDecoder::new(File::open(DATASET).expect("the dataset exists"))
+        .read_to_end(&mut json)
+        .expect("the dataset is valid bzip2");
+    let mut records: Vec<Value> = serde_json::from_slice(&json).expect("the dataset is a JSON array");
+    records.sort_by(|a, b| string(a, "zip_code").cmp(string(b, "zip_code")));
+
+    let mut strings = Strings::default();
+    let mut lists = Vec::new();
+    let mut table = Vec::new();
+    for record in &records {
+        for field in STRING_FIELDS {
+            table.extend_from_slice(&strings.span(string(record, field)));
+        }
+        for field in LIST_FIELDS {
+            let values = record[*field].as_array().unwrap_or_else(|| panic!("{}: {} is not a list", record["zip_code"], field));
+            table.extend_from_slice(&span(lists.len() / SPAN_SIZE, values.len()));
+            for value in values {
+                let value = value.as_str().unwrap_or_else(|| panic!("{}: {} is not a list of strings", record["zip_code"], field));
+                lists.extend_from_slice(&strings.span(value));
+            }
+        }
+        table.push(record["active"].as_bool().unwrap_or_else(|| panic!("{}: active is not a bool", record["zip_code"])) as u8);
+        table.push(position("State", State::VARIANTS, record, "state"));
+        table.push(position("WorldRegion", WorldRegion::VARIANTS, record, "world_region"));
+        table.push(position("ZipcodeType", ZipcodeType::VARIANTS, record, "zip_code_type"));
+    }
+
+    let out = Path::new(&env::var_os("OUT_DIR").unwrap()).to_path_buf();
+    fs::write(out.join("strings.txt"), strings.text).expect("OUT_DIR is writable");
+    fs::write(out.join("lists.bin"), lists).expect("OUT_DIR is writable");
+    fs::write(out.join("records.bin"), table).expect("OUT_DIR is writable");
+}
+
+fn span(offset: usize, len: usize) -> [u8; SPAN_SIZE] {
+    let offset = u32::try_from(offset).expect("offsets fit in a u32").to_le_bytes();
+    let len = u16::try_from(len).expect("lengths fit in a u16").to_le_bytes();
+    [offset[0], offset[1], offset[2], offset[3], len[0], len[1]]
+}
+
+/// Every distinct string in the dataset, stored once.
+#[derive(Default)]
+struct Strings {
+    text: String,
+    spans: HashMap<String, [u8; SPAN_SIZE]>,
+}
+
+impl Strings {
+    fn span(&mut self, s: &str) -> [u8; SPAN_SIZE] {
+        if let Some(span) = self.spans.get(s) {
+            return *span;
+        }
+        let span = span(self.text.len(), s.len());
+        self.text.push_str(s);
+        self.spans.insert(s.to_string(), span);
+        span
+    }
+}
+
+fn string<'a>(record: &'a Value, field: &str) -> &'a str {
+    record[field].as_str().unwrap_or_else(|| panic!("{}: {} is not a string", record["zip_code"], field))
+}
+
+fn position(name: &str, variants: &[(&str, &str)], record: &Value, field: &str) -> u8 {
+    let value = string(record, field);
+    variants.iter()
+        .position(|(_, v)| *v == value)
+        .unwrap_or_else(|| panic!("{}: {:?} is not a known {}", record["zip_code"], value, name)) as u8
+}
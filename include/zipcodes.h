@@ -0,0 +1,232 @@
+#ifndef ZIPCODES_H
+#define ZIPCODES_H
+
+/* Generated with cbindgen from src/ffi.rs. Do not edit by hand. */
+
+#include <stdbool.h>
+#include <stddef.h>
+
+// The status returned by every function in the C API. Apart from `Ok`, `NullPointer` and
+// `Panic`, each code corresponds to a variant of the Rust `Error` enum.
+//
+// The values are part of the ABI and never change: new codes take the next unused value, and
+// `Panic` is always -1.
+typedef enum ZipcodesError {
+  ZIPCODES_ERROR_OK = 0,
+  ZIPCODES_ERROR_NULL_POINTER = 1,
+  ZIPCODES_ERROR_INVALID_FORMAT = 2,
+  ZIPCODES_ERROR_INVALID_CHARACTERS = 3,
+  ZIPCODES_ERROR_EMPTY = 4,
+  ZIPCODES_ERROR_INVALID_SEPARATOR = 5,
+  ZIPCODES_ERROR_MISSING_PLUS4 = 6,
+  ZIPCODES_ERROR_UNKNOWN_ZIPCODE = 7,
+  ZIPCODES_ERROR_MISSING_COORDINATES = 8,
+  ZIPCODES_ERROR_INVALID_RADIUS = 9,
+  ZIPCODES_ERROR_INVALID_COORDINATES = 10,
+  ZIPCODES_ERROR_UNKNOWN_VARIANT = 11,
+  ZIPCODES_ERROR_INVALID_PREFIX_LENGTH = 12,
+  ZIPCODES_ERROR_INVALID_PREFIX_CHARACTERS = 13,
+  ZIPCODES_ERROR_IO = 14,
+  ZIPCODES_ERROR_JSON = 15,
+  ZIPCODES_ERROR_DECOMPRESS = 16,
+  ZIPCODES_ERROR_UTF8 = 17,
+  ZIPCODES_ERROR_SCHEMA = 18,
+  ZIPCODES_ERROR_EMBEDDED = 19,
+  ZIPCODES_ERROR_UNSUPPORTED = 20,
+  ZIPCODES_ERROR_INVALID_RECORD = 21,
+  ZIPCODES_ERROR_INVALID_AREA_CODE = 22,
+  ZIPCODES_ERROR_INVALID_PHONE_NUMBER = 23,
+  ZIPCODES_ERROR_INVALID_COMPACT = 24,
+  ZIPCODES_ERROR_PANIC = -1,
+} ZipcodesError;
+
+// An owned list of zipcode records.
+typedef struct ZipcodesList ZipcodesList;
+
+// An owned zipcode record, along with C copies of its fields that live as long as the handle.
+typedef struct ZipcodesZipcode ZipcodesZipcode;
+
+// Load the embedded database ahead of the first query.
+ZipcodesError zipcodes_init(void);
+
+// Write whether `zipcode` exists in the database to `out`.
+//
+// # Safety
+//
+// `zipcode` must be null or a valid NUL-terminated string, and `out` must be null or valid for
+// writes.
+ZipcodesError zipcodes_is_real(const char *zipcode, bool *out);
+
+// Write a new list of the records matching `zipcode` to `out`. The list may be empty, and must
+// be released with `zipcodes_list_free`.
+//
+// # Safety
+//
+// `zipcode` must be null or a valid NUL-terminated string, and `out` must be null or valid for
+// writes.
+ZipcodesError zipcodes_matching(const char *zipcode, ZipcodesList **out);
+
+// Write a new handle to the record for `zipcode` to `out`, or fail with
+// `ZIPCODES_ERROR_UNKNOWN_ZIPCODE` if there is none. The handle must be released with
+// `zipcodes_zipcode_free`.
+//
+// # Safety
+//
+// `zipcode` must be null or a valid NUL-terminated string, and `out` must be null or valid for
+// writes.
+ZipcodesError zipcodes_lookup(const char *zipcode, ZipcodesZipcode **out);
+
+// A static, human-readable description of an error code.
+const char *zipcodes_error_message(ZipcodesError error);
+
+// The number of records in a list, or 0 for a null list.
+//
+// # Safety
+//
+// `list` must be null or a live handle returned by `zipcodes_matching`.
+size_t zipcodes_list_len(const ZipcodesList *list);
+
+// Borrow the record at `index` in a list, or null if it is out of bounds. The record lives as
+// long as the list and must not be freed on its own.
+//
+// # Safety
+//
+// `list` must be null or a live handle returned by `zipcodes_matching`.
+const ZipcodesZipcode *zipcodes_list_get(const ZipcodesList *list, size_t index);
+
+// Release a list returned by `zipcodes_matching`. Null is ignored.
+//
+// # Safety
+//
+// `list` must be null or a live handle returned by `zipcodes_matching`, and must not be used
+// afterwards.
+void zipcodes_list_free(ZipcodesList *list);
+
+// Release a record returned by `zipcodes_lookup`. Null is ignored.
+//
+// # Safety
+//
+// `zipcode` must be null or a live handle returned by `zipcodes_lookup`, and must not be used
+// afterwards.
+void zipcodes_zipcode_free(ZipcodesZipcode *zipcode);
+
+// The 5-digit zipcode.
+//
+// # Safety
+//
+// `zipcode` must be null or a live record handle. The string lives as long as it.
+const char *zipcodes_zipcode_zip_code(const ZipcodesZipcode *zipcode);
+
+// The zipcode type: "STANDARD", "PO BOX", "UNIQUE" or "MILITARY".
+//
+// # Safety
+//
+// `zipcode` must be null or a live record handle. The string lives as long as it.
+const char *zipcodes_zipcode_type(const ZipcodesZipcode *zipcode);
+
+// The preferred city name.
+//
+// # Safety
+//
+// `zipcode` must be null or a live record handle. The string lives as long as it.
+const char *zipcodes_zipcode_city(const ZipcodesZipcode *zipcode);
+
+// The two-letter state code.
+//
+// # Safety
+//
+// `zipcode` must be null or a live record handle. The string lives as long as it.
+const char *zipcodes_zipcode_state(const ZipcodesZipcode *zipcode);
+
+// The county name, which may be empty.
+//
+// # Safety
+//
+// `zipcode` must be null or a live record handle. The string lives as long as it.
+const char *zipcodes_zipcode_county(const ZipcodesZipcode *zipcode);
+
+// The two-letter country code, which may be empty.
+//
+// # Safety
+//
+// `zipcode` must be null or a live record handle. The string lives as long as it.
+const char *zipcodes_zipcode_country(const ZipcodesZipcode *zipcode);
+
+// The IANA timezone name, which may be empty.
+//
+// # Safety
+//
+// `zipcode` must be null or a live record handle. The string lives as long as it.
+const char *zipcodes_zipcode_timezone(const ZipcodesZipcode *zipcode);
+
+// The world region code, which may be empty.
+//
+// # Safety
+//
+// `zipcode` must be null or a live record handle. The string lives as long as it.
+const char *zipcodes_zipcode_world_region(const ZipcodesZipcode *zipcode);
+
+// The number of telephone area codes serving the zipcode.
+//
+// # Safety
+//
+// `zipcode` must be null or a live record handle.
+size_t zipcodes_zipcode_area_codes_len(const ZipcodesZipcode *zipcode);
+
+// One of the telephone area codes serving the zipcode, or null if `index` is out of bounds.
+//
+// # Safety
+//
+// `zipcode` must be null or a live record handle. The string lives as long as it.
+const char *zipcodes_zipcode_area_code(const ZipcodesZipcode *zipcode, size_t index);
+
+// The number of alternative city names USPS accepts for the zipcode.
+//
+// # Safety
+//
+// `zipcode` must be null or a live record handle.
+size_t zipcodes_zipcode_acceptable_cities_len(const ZipcodesZipcode *zipcode);
+
+// One of the alternative city names USPS accepts for the zipcode, or null if `index` is out of bounds.
+//
+// # Safety
+//
+// `zipcode` must be null or a live record handle. The string lives as long as it.
+const char *zipcodes_zipcode_acceptable_city(const ZipcodesZipcode *zipcode, size_t index);
+
+// The number of city names USPS does not accept for the zipcode.
+//
+// # Safety
+//
+// `zipcode` must be null or a live record handle.
+size_t zipcodes_zipcode_unacceptable_cities_len(const ZipcodesZipcode *zipcode);
+
+// One of the city names USPS does not accept for the zipcode, or null if `index` is out of bounds.
+//
+// # Safety
+//
+// `zipcode` must be null or a live record handle. The string lives as long as it.
+const char *zipcodes_zipcode_unacceptable_city(const ZipcodesZipcode *zipcode, size_t index);
+
+// The latitude of the zipcode's centroid, or NaN if it has no usable coordinates.
+//
+// # Safety
+//
+// `zipcode` must be null or a live record handle.
+double zipcodes_zipcode_lat(const ZipcodesZipcode *zipcode);
+
+// The longitude of the zipcode's centroid, or NaN if it has no usable coordinates.
+//
+// # Safety
+//
+// `zipcode` must be null or a live record handle.
+double zipcodes_zipcode_long(const ZipcodesZipcode *zipcode);
+
+// Whether the zipcode is still in use.
+//
+// # Safety
+//
+// `zipcode` must be null or a live record handle.
+bool zipcodes_zipcode_active(const ZipcodesZipcode *zipcode);
+
+#endif /* ZIPCODES_H */
@@ -0,0 +1,187 @@
+//! A Rust-backed drop-in replacement for the `zipcodes` Python package.
+//!
+//! Records are returned as dicts with exactly the keys and values of the Python package, and
+//! invalid zipcodes raise `ValueError` with the message of the corresponding `zipcodes::Error`.
+//! Arguments that are not strings raise `TypeError`, as they do in the Python package.
+
+// PyO3 0.22's `#[pyfunction]` expansion converts `PyResult` errors into `PyErr` again.
+#![allow(clippy::useless_conversion)]
+
+use pyo3::exceptions::{PyKeyError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyString};
+
+use zipcodes::{Error, Zipcode};
+
+fn value_error(e: Error) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+/// Extract a zipcode argument, raising `TypeError` for anything that is not a string.
+fn zipcode_arg(zipcode: &Bound<'_, PyAny>) -> PyResult<String> {
+    match zipcode.downcast::<PyString>() {
+        Ok(zipcode) => Ok(zipcode.to_str()?.to_string()),
+        Err(_) => Err(PyTypeError::new_err("Invalid type, zipcode must be a string.")),
+    }
+}
+
+/// Build a dict with the keys and values of a record in the Python package.
+fn to_dict<'py>(py: Python<'py>, zipcode: &Zipcode) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    dict.set_item("acceptable_cities", &zipcode.acceptable_cities)?;
+    dict.set_item("active", zipcode.active)?;
+    dict.set_item("area_codes", &zipcode.area_codes)?;
+    dict.set_item("city", zipcode.city.as_str())?;
+    dict.set_item("country", zipcode.country.as_str())?;
+    dict.set_item("county", zipcode.county.as_str())?;
+    dict.set_item("lat", zipcode.lat.as_str())?;
+    dict.set_item("long", zipcode.long.as_str())?;
+    dict.set_item("state", zipcode.state.as_str())?;
+    dict.set_item("timezone", zipcode.timezone.as_str())?;
+    dict.set_item("unacceptable_cities", &zipcode.unacceptable_cities)?;
+    dict.set_item("world_region", zipcode.world_region.as_str())?;
+    dict.set_item("zip_code", zipcode.zip_code.as_str())?;
+    dict.set_item("zip_code_type", zipcode.zip_code_type.as_str())?;
+    Ok(dict)
+}
+
+/// Convert records into a list of dicts shaped like those of the Python package.
+fn to_py<'a, I: IntoIterator<Item = &'a Zipcode>>(py: Python<'_>, zipcodes: I) -> PyResult<PyObject> {
+    let dicts = zipcodes.into_iter().map(|z| to_dict(py, z)).collect::<PyResult<Vec<_>>>()?;
+    Ok(PyList::new_bound(py, dicts).into_any().unbind())
+}
+
+/// Extract the value of a key of a record dict, raising `KeyError` if it is missing.
+fn item<'py, T: FromPyObject<'py>>(dict: &Bound<'py, PyDict>, key: &str) -> PyResult<T> {
+    dict.get_item(key)?.ok_or_else(|| PyKeyError::new_err(key.to_string()))?.extract()
+}
+
+/// Convert a dict shaped like those returned by this module back into a record.
+fn from_dict(zipcode: &Bound<'_, PyAny>) -> PyResult<Zipcode> {
+    let dict = zipcode.downcast::<PyDict>()?;
+    Ok(Zipcode {
+        acceptable_cities: item(dict, "acceptable_cities")?,
+        active: item(dict, "active")?,
+        area_codes: item(dict, "area_codes")?,
+        city: item(dict, "city")?,
+        country: item(dict, "country")?,
+        county: item(dict, "county")?,
+        lat: item(dict, "lat")?,
+        long: item(dict, "long")?,
+        state: item::<String>(dict, "state")?.parse().map_err(value_error)?,
+        timezone: item(dict, "timezone")?,
+        unacceptable_cities: item(dict, "unacceptable_cities")?,
+        world_region: item::<String>(dict, "world_region")?.parse().map_err(value_error)?,
+        zip_code: item(dict, "zip_code")?,
+        zip_code_type: item::<String>(dict, "zip_code_type")?.parse().map_err(value_error)?,
+    })
+}
+
+/// Convert an optional list of zipcode dicts, such as one returned by this module, into records.
+fn zips_arg(zips: Option<&Bound<'_, PyAny>>) -> PyResult<Option<Vec<Zipcode>>> {
+    match zips {
+        Some(zips) if !zips.is_none() => zips.iter()?.map(|z| from_dict(&z?)).collect::<PyResult<Vec<_>>>().map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// A keyword argument of `filter_by`, compared against one field of every record.
+enum FieldFilter {
+    Str(fn(&Zipcode) -> &str, String),
+    Bool(bool),
+    List(fn(&Zipcode) -> &[String], Vec<String>),
+    /// A value of a different type than the field, which like in Python never compares equal.
+    Never,
+}
+
+impl FieldFilter {
+    fn new(field: &str, value: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let string = |get: fn(&Zipcode) -> &str| value.extract().map_or(FieldFilter::Never, |v| FieldFilter::Str(get, v));
+        let list = |get: fn(&Zipcode) -> &[String]| value.extract().map_or(FieldFilter::Never, |v| FieldFilter::List(get, v));
+        Ok(match field {
+            "acceptable_cities" => list(|z| &z.acceptable_cities),
+            "active" => value.extract().map_or(FieldFilter::Never, FieldFilter::Bool),
+            "area_codes" => list(|z| &z.area_codes),
+            "city" => string(|z| &z.city),
+            "country" => string(|z| &z.country),
+            "county" => string(|z| &z.county),
+            "lat" => string(|z| &z.lat),
+            "long" => string(|z| &z.long),
+            "state" => string(|z| z.state.as_str()),
+            "timezone" => string(|z| &z.timezone),
+            "unacceptable_cities" => list(|z| &z.unacceptable_cities),
+            "world_region" => string(|z| z.world_region.as_str()),
+            "zip_code" => string(|z| &z.zip_code),
+            "zip_code_type" => string(|z| z.zip_code_type.as_str()),
+            _ => return Err(PyKeyError::new_err(field.to_string())),
+        })
+    }
+
+    fn matches(&self, zipcode: &Zipcode) -> bool {
+        match self {
+            FieldFilter::Str(get, value) => get(zipcode) == value,
+            FieldFilter::Bool(value) => zipcode.active == *value,
+            FieldFilter::List(get, value) => get(zipcode) == value.as_slice(),
+            FieldFilter::Never => false,
+        }
+    }
+}
+
+/// Return the zipcodes matching the supplied zipcode, of the format "#####" or "#####-####".
+#[pyfunction]
+#[pyo3(signature = (zipcode, zips=None))]
+fn matching(py: Python<'_>, zipcode: &Bound<'_, PyAny>, zips: Option<&Bound<'_, PyAny>>) -> PyResult<PyObject> {
+    let zipcode = zipcode_arg(zipcode)?;
+    let zips = zips_arg(zips)?;
+    to_py(py, &zipcodes::matching(zipcode.as_str(), zips).map_err(value_error)?)
+}
+
+/// Return whether the supplied zipcode exists in the database.
+#[pyfunction]
+fn is_real(zipcode: &Bound<'_, PyAny>) -> PyResult<bool> {
+    zipcodes::is_real(zipcode_arg(zipcode)?.as_str()).map_err(value_error)
+}
+
+/// Return the zipcodes beginning with the supplied prefix.
+#[pyfunction]
+#[pyo3(signature = (partial_zipcode, zips=None))]
+fn similar_to(py: Python<'_>, partial_zipcode: &Bound<'_, PyAny>, zips: Option<&Bound<'_, PyAny>>) -> PyResult<PyObject> {
+    let prefix = zipcode_arg(partial_zipcode)?;
+    let zips = zips_arg(zips)?;
+    to_py(py, &zipcodes::similar_to(&prefix, zips).map_err(value_error)?)
+}
+
+/// Return the zipcodes whose fields equal every supplied keyword argument, e.g.
+/// `filter_by(city="Old Saybrook", active=True)`. An unknown field raises `KeyError`.
+#[pyfunction]
+#[pyo3(signature = (zips=None, **kwargs))]
+fn filter_by(py: Python<'_>, zips: Option<&Bound<'_, PyAny>>, kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<PyObject> {
+    let filters = match kwargs {
+        Some(kwargs) => kwargs.iter()
+            .map(|(field, value)| FieldFilter::new(field.downcast::<PyString>()?.to_str()?, &value))
+            .collect::<PyResult<Vec<_>>>()?,
+        None => Vec::new(),
+    };
+    let filter = |z: &Zipcode| filters.iter().all(|f| f.matches(z));
+    match zips_arg(zips)? {
+        Some(zips) => to_py(py, &zipcodes::filter_by(vec![filter], Some(zips)).map_err(value_error)?),
+        None => to_py(py, zipcodes::filter_by_ref(vec![filter]).map_err(value_error)?),
+    }
+}
+
+/// Return every zipcode in the database.
+#[pyfunction]
+fn list_all(py: Python<'_>) -> PyResult<PyObject> {
+    to_py(py, zipcodes::iter().map_err(value_error)?)
+}
+
+#[pymodule]
+#[pyo3(name = "zipcodes")]
+fn zipcodes_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(matching, m)?)?;
+    m.add_function(wrap_pyfunction!(is_real, m)?)?;
+    m.add_function(wrap_pyfunction!(similar_to, m)?)?;
+    m.add_function(wrap_pyfunction!(filter_by, m)?)?;
+    m.add_function(wrap_pyfunction!(list_all, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,50 @@
+import pytest
+
+import zipcodes
+
+
+def test_matching():
+    (cypress,) = zipcodes.matching("77429-1145")
+    assert cypress == {
+        "acceptable_cities": [],
+        "active": True,
+        "area_codes": ["281", "832"],
+        "city": "Cypress",
+        "country": "US",
+        "county": "Harris County",
+        "lat": "29.9857",
+        "long": "-95.6548",
+        "state": "TX",
+        "timezone": "America/Chicago",
+        "unacceptable_cities": [],
+        "world_region": "NA",
+        "zip_code": "77429",
+        "zip_code_type": "STANDARD",
+    }
+    assert zipcodes.matching("06463") == []
+
+
+def test_errors():
+    with pytest.raises(ValueError, match="Invalid characters"):
+        zipcodes.matching("0646a")
+    with pytest.raises(ValueError, match="Invalid format"):
+        zipcodes.matching("064690")
+    with pytest.raises(TypeError, match="Invalid type, zipcode must be a string."):
+        zipcodes.matching(None)
+
+
+def test_is_real():
+    assert not zipcodes.is_real("06463")
+    assert zipcodes.is_real("06469")
+
+
+def test_similar_to_and_filter_by():
+    assert [z["zip_code"] for z in zipcodes.similar_to("1018")] == ["10184", "10185"]
+    assert [z["zip_code"] for z in zipcodes.filter_by(city="Old Saybrook")] == ["06475"]
+    windsor = zipcodes.similar_to("2", zips=zipcodes.filter_by(active=True, city="Windsor"))
+    assert [z["zip_code"] for z in windsor] == ["23487", "27983", "29856"]
+    assert len(zipcodes.list_all()) == len(zipcodes.filter_by())
+    assert zipcodes.filter_by(city="Old Saybrook", state="TX") == []
+    assert zipcodes.filter_by(active="yes") == []
+    with pytest.raises(KeyError):
+        zipcodes.filter_by(town="Old Saybrook")
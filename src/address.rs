@@ -0,0 +1,220 @@
+use serde::Serialize;
+
+use crate::{try_load, Result, State, ToZip5, Zipcode, ZipcodeDb};
+
+/// The most suggestions `validate_address_parts` makes from other zipcodes in the same state.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// The city and state to fill in for a zipcode, along with the other city names that USPS does
+/// and does not accept for it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct CityState<'a> {
+    /// The USPS preferred city name, which is always acceptable.
+    pub city: &'a str,
+    pub state: State,
+    /// Alternate city names USPS also accepts in addresses for the zipcode.
+    pub acceptable_cities: &'a [String],
+    /// City names associated with the zipcode, such as neighborhoods or former names, that USPS
+    /// does not accept in addresses for it.
+    pub unacceptable_cities: &'a [String],
+}
+
+impl<'a> CityState<'a> {
+    /// Every city name USPS accepts for the zipcode, starting with the preferred one.
+    pub fn acceptable_names(&self) -> impl Iterator<Item = &'a str> {
+        std::iter::once(self.city).chain(self.acceptable_cities.iter().map(String::as_str))
+    }
+
+    /// Whether USPS accepts the supplied city name for the zipcode, ignoring case and punctuation.
+    pub fn is_acceptable(&self, city: &str) -> bool {
+        let city = normalize_city(city);
+        self.acceptable_names().any(|name| normalize_city(name) == city)
+    }
+}
+
+/// How a city, state and zipcode combination compares to the USPS data for the zipcode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressMatch {
+    /// The city is the USPS preferred name for the zipcode, in the right state.
+    Preferred,
+    /// The city is an alternate name USPS accepts for the zipcode, in the right state.
+    AcceptableAlias,
+    /// The city is associated with the zipcode, but USPS does not accept it in addresses.
+    UnacceptableAlias,
+    /// The city belongs to the zipcode, but the zipcode is in a different state.
+    WrongState,
+    /// The zipcode does not exist, or the city is not associated with it.
+    Unknown,
+}
+
+/// The result of `validate_address_parts`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Validation<'a> {
+    pub status: AddressMatch,
+    /// The city and state of the zipcode, if it exists.
+    pub expected: Option<CityState<'a>>,
+    /// Corrected combinations, best first. Empty when `status` is `AddressMatch::Preferred`.
+    pub suggestions: Vec<Suggestion<'a>>,
+}
+
+/// A corrected city, state and zipcode combination.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Suggestion<'a> {
+    pub city: &'a str,
+    pub state: State,
+    pub zip_code: &'a str,
+}
+
+impl Validation<'_> {
+    /// Whether USPS accepts the combination as it is.
+    pub fn is_valid(&self) -> bool {
+        matches!(self.status, AddressMatch::Preferred | AddressMatch::AcceptableAlias)
+    }
+}
+
+impl Zipcode {
+    /// The city and state to fill in for the zipcode.
+    pub fn city_state(&self) -> CityState<'_> {
+        CityState {
+            city: &self.city,
+            state: self.state,
+            acceptable_cities: &self.acceptable_cities,
+            unacceptable_cities: &self.unacceptable_cities,
+        }
+    }
+}
+
+impl ZipcodeDb {
+    /// The city and state to fill in for the supplied zipcode, as in `crate::city_state`.
+    pub fn city_state<Z: ToZip5>(&self, zipcode: Z) -> Result<Option<CityState<'_>>> {
+        Ok(self.get(zipcode)?.map(Zipcode::city_state))
+    }
+
+    /// Classify a city, state and zipcode combination, as in `crate::validate_address_parts`.
+    pub fn validate_address_parts<Z: ToZip5>(&self, city: &str, state: &str, zipcode: Z) -> Result<Validation<'_>> {
+        let zipcode = self.get(zipcode)?;
+        let city = normalize_city(city);
+        let state = normalize_state(state);
+        let expected = zipcode.map(Zipcode::city_state);
+        let status = match expected {
+            None => AddressMatch::Unknown,
+            Some(expected) => {
+                let is = |names: &[String]| names.iter().any(|n| normalize_city(n) == city);
+                let status = if normalize_city(expected.city) == city {
+                    AddressMatch::Preferred
+                } else if is(expected.acceptable_cities) {
+                    AddressMatch::AcceptableAlias
+                } else if is(expected.unacceptable_cities) {
+                    AddressMatch::UnacceptableAlias
+                } else {
+                    AddressMatch::Unknown
+                };
+                match state == Some(expected.state) {
+                    false if status != AddressMatch::Unknown => AddressMatch::WrongState,
+                    _ => status,
+                }
+            }
+        };
+
+        let mut suggestions = Vec::new();
+        if status != AddressMatch::Preferred {
+            if let Some(zipcode) = zipcode {
+                suggestions.push(Suggestion { city: &zipcode.city, state: zipcode.state, zip_code: &zipcode.zip_code });
+            }
+        }
+        if let (AddressMatch::WrongState | AddressMatch::Unknown, Some(state)) = (status, state) {
+            // The zipcode may be the mistake instead, so look for the city in the supplied state.
+            suggestions.extend(self.zipcodes()
+                .iter()
+                .filter(|z| z.state == state && z.active && normalize_city(&z.city) == city)
+                .take(MAX_SUGGESTIONS)
+                .map(|z| Suggestion { city: &z.city, state: z.state, zip_code: &z.zip_code }));
+        }
+        Ok(Validation { status, expected, suggestions })
+    }
+}
+
+/// The city and state to fill in for the supplied zipcode, or `None` if it does not exist.
+///
+/// The supplied zipcode may be in any of the formats accepted by `matching`, including ZIP+4.
+pub fn city_state<Z: ToZip5>(zipcode: Z) -> Result<Option<CityState<'static>>> {
+    try_load()?.city_state(zipcode)
+}
+
+/// Classify whether a city, state and zipcode form a combination USPS accepts, such as
+/// "Old Saybrook, CT 06475", and suggest corrections if they do not.
+///
+/// City names and state codes are compared ignoring case and punctuation, so "st. louis" matches
+/// "St Louis". The state may also be spelled out, as in "Connecticut" or "new york". The zipcode
+/// may be in any of the formats accepted by `matching`.
+pub fn validate_address_parts<Z: ToZip5>(city: &str, state: &str, zipcode: Z) -> Result<Validation<'static>> {
+    try_load()?.validate_address_parts(city, state, zipcode)
+}
+
+/// Fold a city name to lowercase words separated by single spaces, treating punctuation as a
+/// word break.
+pub(crate) fn normalize_city(city: &str) -> String {
+    city.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Parse a state code or full state name, ignoring case, whitespace and punctuation. Names are
+/// matched against the `State` variants, so "District of Columbia" is `DistrictOfColumbia`.
+pub(crate) fn normalize_state(state: &str) -> Option<State> {
+    let state = state.chars().filter(|c| c.is_alphanumeric()).collect::<String>();
+    state.parse().ok().or_else(|| State::ALL.iter().copied().find(|s| format!("{:?}", s).eq_ignore_ascii_case(&state)))
+}
+
+#[cfg(all(test, any(feature = "bzip2", feature = "static-data")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_fill_in_city_and_state() {
+        let old_saybrook = city_state("06475-1234").unwrap().unwrap();
+        assert_eq!((old_saybrook.city, old_saybrook.state), ("Old Saybrook", State::Connecticut));
+        assert_eq!(old_saybrook.unacceptable_cities, ["Fenwick"]);
+        assert!(old_saybrook.is_acceptable("old saybrook"));
+        assert!(!old_saybrook.is_acceptable("Fenwick"));
+
+        let windsor = city_state("27983").unwrap().unwrap();
+        assert_eq!(windsor.acceptable_names().collect::<Vec<_>>(), ["Windsor", "Askewville"]);
+        assert!(windsor.is_acceptable("Askewville"));
+
+        assert_eq!(city_state("06463").unwrap(), None);
+        assert!(city_state("0646a").is_err());
+    }
+
+    #[test]
+    fn should_classify_address_parts() {
+        let status = |city, state, zip| validate_address_parts(city, state, zip).unwrap().status;
+        assert_eq!(status("Old Saybrook", "CT", "06475"), AddressMatch::Preferred);
+        assert_eq!(status("old  saybrook.", "c.t.", "06475-1234"), AddressMatch::Preferred);
+        assert_eq!(status("Askewville", "NC", "27983"), AddressMatch::AcceptableAlias);
+        assert_eq!(status("Fenwick", "CT", "06475"), AddressMatch::UnacceptableAlias);
+        assert_eq!(status("Old Saybrook", "NY", "06475"), AddressMatch::WrongState);
+        assert_eq!(status("Cypress", "CT", "06475"), AddressMatch::Unknown);
+        assert_eq!(status("Old Saybrook", "CT", "06463"), AddressMatch::Unknown);
+        assert!(validate_address_parts("Old Saybrook", "CT", "0646a").is_err());
+
+        assert_eq!(status("Old Saybrook", "Connecticut", "06475"), AddressMatch::Preferred);
+        assert_eq!(status("Old Saybrook", " new york ", "06475"), AddressMatch::WrongState);
+        assert_eq!(normalize_state("District of Columbia"), Some(State::DistrictOfColumbia));
+        assert_eq!(normalize_state("Conn."), None);
+
+        let fenwick = validate_address_parts("Fenwick", "CT", "06475").unwrap();
+        assert!(!fenwick.is_valid());
+        assert_eq!(fenwick.suggestions, [Suggestion { city: "Old Saybrook", state: State::Connecticut, zip_code: "06475" }]);
+        assert!(validate_address_parts("Old Saybrook", "CT", "06475").unwrap().suggestions.is_empty());
+
+        let wrong_zip = validate_address_parts("Cypress", "TX", "06475").unwrap();
+        assert_eq!(wrong_zip.expected.unwrap().city, "Old Saybrook");
+        assert!(wrong_zip.suggestions[1..].iter().any(|s| s.zip_code == "77429"));
+        assert!(wrong_zip.suggestions.len() <= 1 + MAX_SUGGESTIONS);
+        assert_eq!(normalize_city(" Winston-Salem "), "winston salem");
+    }
+}
@@ -0,0 +1,194 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::address::normalize_city;
+use crate::{clean_prefix, try_load, Result, State, Zipcode, ZipcodeDb};
+
+/// A city offered by `autocomplete`, with its zipcodes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Completion<'a> {
+    pub city: &'a str,
+    pub state: State,
+    /// The active zipcodes whose preferred city this is, in order. For a zipcode prefix query,
+    /// only those beginning with the prefix.
+    pub zip_codes: Vec<&'a str>,
+}
+
+impl fmt::Display for Completion<'_> {
+    /// Formats the completion as it would appear in a search box, such as
+    /// "San Francisco, CA (94102–94188)".
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}, {}", self.city, self.state)?;
+        match self.zip_codes.as_slice() {
+            [] => Ok(()),
+            [zip_code] => write!(f, " ({})", zip_code),
+            [first, .., last] => write!(f, " ({}–{})", first, last),
+        }
+    }
+}
+
+/// A prefix index over the distinct city and state pairs of the active zipcodes in a database.
+///
+/// Cities are ranked by popularity, approximated by how many active zipcodes they have, and
+/// stored in rank order, so that the best completions for a query are the lowest ranks among the
+/// keys beginning with it. Keys are the normalized city names, sorted so that those beginning
+/// with a prefix form a contiguous run.
+pub(crate) struct AutocompleteIndex {
+    cities: Vec<City>,
+    keys: Vec<(String, usize)>,
+    /// The rank of the city of each zipcode, by position in the database, if it is active.
+    ranks: Vec<Option<usize>>,
+}
+
+struct City {
+    /// The position of the city's first zipcode in the database.
+    first: usize,
+    state: State,
+    zipcodes: Vec<usize>,
+}
+
+impl AutocompleteIndex {
+    pub(crate) fn new(zipcodes: &[Zipcode]) -> Self {
+        let mut positions = HashMap::new();
+        let mut cities = Vec::<City>::new();
+        for (i, zipcode) in zipcodes.iter().enumerate().filter(|(_, z)| z.active) {
+            let city = *positions.entry((zipcode.city.as_str(), zipcode.state)).or_insert_with(|| {
+                cities.push(City { first: i, state: zipcode.state, zipcodes: Vec::new() });
+                cities.len() - 1
+            });
+            cities[city].zipcodes.push(i);
+        }
+        let name = |city: &City| (&zipcodes[city.first].city, city.state);
+        cities.sort_by(|a, b| b.zipcodes.len().cmp(&a.zipcodes.len()).then_with(|| name(a).cmp(&name(b))));
+
+        let mut ranks = vec![None; zipcodes.len()];
+        for (rank, city) in cities.iter().enumerate() {
+            for &i in &city.zipcodes {
+                ranks[i] = Some(rank);
+            }
+        }
+        let mut keys = cities.iter()
+            .enumerate()
+            .map(|(rank, city)| (normalize_city(&zipcodes[city.first].city), rank))
+            .collect::<Vec<_>>();
+        keys.sort_unstable();
+        AutocompleteIndex { cities, keys, ranks }
+    }
+
+    /// The ranks of the cities whose names begin with the normalized prefix, best first.
+    fn cities(&self, prefix: &str, state: Option<&str>, limit: usize) -> Vec<usize> {
+        let start = self.keys.partition_point(|(key, _)| key.as_str() < prefix);
+        let len = self.keys[start..].partition_point(|(key, _)| key.starts_with(prefix));
+        let mut ranks = self.keys[start..start + len]
+            .iter()
+            .map(|&(_, rank)| rank)
+            .filter(|&rank| state.is_none_or(|s| self.cities[rank].state.as_str().starts_with(s)))
+            .collect::<Vec<_>>();
+        if ranks.len() > limit {
+            ranks.select_nth_unstable(limit);
+            ranks.truncate(limit);
+        }
+        ranks.sort_unstable();
+        ranks
+    }
+
+    fn completion<'a>(&self, zipcodes: &'a [Zipcode], rank: usize, zip_codes: Vec<&'a str>) -> Completion<'a> {
+        let city = &self.cities[rank];
+        Completion { city: &zipcodes[city.first].city, state: city.state, zip_codes }
+    }
+}
+
+impl ZipcodeDb {
+    /// Suggest cities for a partially typed city name or zipcode, as in `crate::autocomplete`.
+    pub fn autocomplete(&self, query: &str, limit: usize) -> Vec<Completion<'_>> {
+        let index = self.autocomplete_index();
+        let zipcodes = self.zipcodes();
+
+        if query.trim_start().starts_with(|c: char| c.is_ascii_digit()) {
+            let prefix = match clean_prefix(query) {
+                Ok(prefix) => prefix,
+                Err(_) => return Vec::new(),
+            };
+            let start = zipcodes.partition_point(|z| z.zip_code.as_str() < prefix);
+            let len = zipcodes[start..].partition_point(|z| z.zip_code.starts_with(prefix));
+            let matching = start..start + len;
+            let mut ranks = index.ranks[matching.clone()].iter().flatten().copied().collect::<Vec<_>>();
+            ranks.sort_unstable();
+            ranks.dedup();
+            ranks.truncate(limit);
+            let mut cities = ranks.into_iter().map(|rank| (rank, Vec::new())).collect::<Vec<(usize, Vec<&str>)>>();
+            for i in matching {
+                if let Some(Ok(city)) = index.ranks[i].map(|rank| cities.binary_search_by_key(&rank, |(rank, _)| *rank)) {
+                    cities[city].1.push(&zipcodes[i].zip_code);
+                }
+            }
+            return cities.into_iter().map(|(rank, zip_codes)| index.completion(zipcodes, rank, zip_codes)).collect();
+        }
+
+        let (city, state) = match query.split_once(',') {
+            Some((city, state)) => (city, Some(state.trim().to_ascii_uppercase())),
+            None => (query, None),
+        };
+        let prefix = normalize_city(city);
+        if prefix.is_empty() {
+            return Vec::new();
+        }
+        index.cities(&prefix, state.as_deref(), limit)
+            .into_iter()
+            .map(|rank| {
+                let zip_codes = index.cities[rank].zipcodes.iter().map(|&i| zipcodes[i].zip_code.as_str()).collect();
+                index.completion(zipcodes, rank, zip_codes)
+            })
+            .collect()
+    }
+}
+
+/// Suggest up to `limit` cities for a partially typed query, most popular first, each with its
+/// active zipcodes, such as "San Francisco, CA (94102–94188)" for "San Fr".
+///
+/// A query starting with a digit is treated as a zipcode prefix, and suggests the cities with
+/// zipcodes beginning with it. Otherwise it is matched against the beginning of city names,
+/// ignoring case and punctuation, and may end with a comma and the beginning of a state code, as
+/// in "Springfield, I". Popularity is approximated by how many active zipcodes a city has.
+///
+/// The index behind the suggestions is built on first use, after which queries take
+/// microseconds.
+pub fn autocomplete(query: &str, limit: usize) -> Result<Vec<Completion<'static>>> {
+    Ok(try_load()?.autocomplete(query, limit))
+}
+
+#[cfg(all(test, any(feature = "bzip2", feature = "static-data")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_complete_city_names() {
+        let san_fr = autocomplete("San Fr", 3).unwrap();
+        assert_eq!(san_fr[0].to_string(), "San Francisco, CA (94102–94188)");
+        assert!(san_fr.iter().all(|c| c.city.starts_with("San Fr")));
+
+        let springfield = autocomplete("springfield, i", 10).unwrap();
+        assert!(springfield.len() >= 2);
+        assert!(springfield.iter().all(|c| c.city == "Springfield" && c.state.as_str().starts_with('I')));
+        assert!(springfield.windows(2).all(|w| w[0].zip_codes.len() >= w[1].zip_codes.len()));
+
+        assert_eq!(autocomplete("Old Saybrook", 10).unwrap()[0].to_string(), "Old Saybrook, CT (06475)");
+        assert_eq!(autocomplete("Qwxzv", 10).unwrap(), []);
+        assert_eq!(autocomplete(" ,", 10).unwrap(), []);
+    }
+
+    #[test]
+    fn should_complete_zipcode_prefixes() {
+        let cypress = autocomplete("7742", 10).unwrap();
+        let cypress = cypress.iter().find(|c| c.city == "Cypress").unwrap();
+        assert!(cypress.zip_codes.contains(&"77429"));
+        assert!(cypress.zip_codes.iter().all(|z| z.starts_with("7742")));
+
+        // Every active zipcode under the prefix belongs to exactly one completion.
+        let zip_codes = autocomplete("1018", 10).unwrap().into_iter().flat_map(|c| c.zip_codes).collect::<Vec<_>>();
+        assert_eq!(zip_codes, ["10185"]);
+        assert_eq!(autocomplete("1a", 10).unwrap(), []);
+        assert_eq!(autocomplete("941021", 10).unwrap(), []);
+    }
+}
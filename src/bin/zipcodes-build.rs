@@ -0,0 +1,72 @@
+//! Builds the zipcode dataset from raw source files.
+//!
+//! Run `zipcodes-build --help` for usage.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::process;
+
+use zipcodes::{DatasetBuilder, Format};
+
+const USAGE: &str = "\
+Build the zipcode dataset from source CSVs in the format of the zip code database published by
+unitedstateszipcodes.org, validating and de-duplicating their records.
+
+USAGE:
+    zipcodes-build --output <PATH> <SOURCE>...
+
+Sources are read in priority order: rows identical to an earlier one are dropped as duplicates,
+and rows disagreeing with an earlier one for the same zipcode are dropped as conflicts.
+
+OPTIONS:
+    --output <PATH>         Where to write the dataset, in the format given by its extension:
+                            .json.bz2, .json, .ndjson or .bin. Required, and overwritten if it
+                            exists
+    --help                  Print this message
+";
+
+fn main() {
+    let mut output = None;
+    let mut sources = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--help" | "-h" => {
+                print!("{}", USAGE);
+                return;
+            }
+            "--output" | "-o" => output = Some(args.next().unwrap_or_else(|| exit_usage("missing value for --output"))),
+            _ if arg.starts_with('-') => exit_usage(&format!("unknown option {}", arg)),
+            _ => sources.push(arg),
+        }
+    }
+    let output = output.unwrap_or_else(|| exit_usage("--output is required"));
+    if sources.is_empty() {
+        exit_usage("at least one source is required");
+    }
+
+    let mut builder = DatasetBuilder::new();
+    for source in &sources {
+        if let Err(e) = builder.read_csv_path(source) {
+            fail(&format!("{}: {}", source, e));
+        }
+    }
+    let (db, stats) = builder.build();
+    let written = File::create(&output)
+        .map_err(zipcodes::Error::from)
+        .and_then(|file| db.to_writer(BufWriter::new(file), Format::from_path(&output)));
+    if let Err(e) = written {
+        fail(&format!("{}: {}", output, e));
+    }
+    eprintln!("{}\nwrote {}", stats, output);
+}
+
+fn exit_usage(message: &str) -> ! {
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    process::exit(2);
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("error: {}", message);
+    process::exit(1);
+}
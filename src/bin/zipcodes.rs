@@ -0,0 +1,375 @@
+//! Command-line access to the zipcode database.
+//!
+//! Run `zipcodes help` for usage.
+
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+use std::process;
+use std::str::FromStr;
+
+use zipcodes::{State, Unit, Zipcode, ZipcodeType};
+
+const USAGE: &str = "\
+Query U.S. zipcodes from the shell.
+
+USAGE:
+    zipcodes <COMMAND> [OPTIONS]
+
+COMMANDS:
+    lookup <ZIPCODE>...     Print the records matching each zipcode
+    validate                Read zipcodes from stdin, one per line, and report invalid or unknown ones
+    near <ZIPCODE>          Print the zipcodes within --radius of a zipcode, closest first
+    filter                  Print the zipcodes matching every supplied filter
+    export                  Print every zipcode in the database
+    help                    Print this message
+
+OPTIONS:
+    --format <FORMAT>       Output format: json, csv or table [default: table]
+    --radius <DISTANCE>     (near) Search radius [default: 10]
+    --unit <UNIT>           (near) Unit of the radius and distances: mi or km [default: mi]
+    --state <STATE>         (filter) Two-letter state code, e.g. TX
+    --city <CITY>           (filter) Preferred city name, ignoring case
+    --county <COUNTY>       (filter) County name, ignoring case
+    --type <TYPE>           (filter) Zipcode type: STANDARD, \"PO BOX\", UNIQUE or MILITARY
+    --active <BOOL>         (filter) Only active (true) or inactive (false) zipcodes
+";
+
+/// The columns shown for zipcode records in CSV and table output.
+const ZIPCODE_COLUMNS: &[&str] = &[
+    "zip_code", "zip_code_type", "active", "city", "state", "county", "lat", "long", "timezone", "area_codes",
+];
+
+type Filter = Box<dyn Fn(&Zipcode) -> bool>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Json,
+    Csv,
+    Table,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "table" => Ok(Format::Table),
+            _ => Err(format!("unknown format {:?}, expected json, csv or table", s)),
+        }
+    }
+}
+
+/// The parsed command line: a command, its positional arguments and its `--name value` options.
+#[derive(Debug, Default)]
+struct Args {
+    command: String,
+    positional: Vec<String>,
+    options: Vec<(String, String)>,
+}
+
+impl Args {
+    fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Args, String> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--help" || arg == "-h" {
+                // Takes no value, and prints usage whatever else is on the command line.
+                return Ok(Args { command: "help".to_string(), ..Args::default() });
+            } else if let Some(option) = arg.strip_prefix("--") {
+                let (name, value) = match option.split_once('=') {
+                    Some((name, value)) => (name.to_string(), value.to_string()),
+                    None => {
+                        let value = args.next().ok_or_else(|| format!("missing value for --{}", option))?;
+                        (option.to_string(), value)
+                    }
+                };
+                parsed.options.push((name, value));
+            } else if parsed.command.is_empty() {
+                parsed.command = arg;
+            } else {
+                parsed.positional.push(arg);
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.iter().rev().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+
+    fn parsed_option<T: FromStr>(&self, name: &str) -> Result<Option<T>, String>
+    where
+        T::Err: std::fmt::Display,
+    {
+        self.option(name)
+            .map(|v| v.parse::<T>().map_err(|e| format!("invalid --{} {:?}: {}", name, v, e)))
+            .transpose()
+    }
+
+    fn check_options(&self, allowed: &[&str]) -> Result<(), String> {
+        match self.options.iter().find(|(n, _)| n != "format" && !allowed.contains(&n.as_str())) {
+            Some((name, _)) => Err(format!("unexpected option --{} for {}", name, self.command)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The result of a command: rows of JSON objects, the columns to show for them outside of JSON
+/// output, and whether the command found a problem that should fail the process.
+struct Output {
+    columns: Vec<&'static str>,
+    rows: Vec<Value>,
+    failed: bool,
+}
+
+impl Output {
+    fn zipcodes<'a, I: IntoIterator<Item = &'a Zipcode>>(zipcodes: I) -> Output {
+        Output {
+            columns: ZIPCODE_COLUMNS.to_vec(),
+            rows: zipcodes.into_iter().map(|z| serde_json::to_value(z).expect("zipcodes serialize to JSON")).collect(),
+            failed: false,
+        }
+    }
+}
+
+fn main() {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => exit_usage(&e),
+    };
+    let format = match args.parsed_option::<Format>("format") {
+        Ok(format) => format.unwrap_or(Format::Table),
+        Err(e) => exit_usage(&e),
+    };
+    let output = match args.command.as_str() {
+        "lookup" => lookup(&args),
+        "validate" => validate(&args),
+        "near" => near(&args),
+        "filter" => filter(&args),
+        "export" => export(&args),
+        "" | "help" => {
+            print!("{}", USAGE);
+            return;
+        }
+        command => exit_usage(&format!("unknown command {:?}", command)),
+    };
+    let output = match output {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(2);
+        }
+    };
+    let stdout = io::stdout();
+    if let Err(e) = render(&output, format, &mut stdout.lock()) {
+        if e.kind() != io::ErrorKind::BrokenPipe {
+            eprintln!("error: {}", e);
+            process::exit(2);
+        }
+    }
+    if output.failed {
+        process::exit(1);
+    }
+}
+
+fn exit_usage(message: &str) -> ! {
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    process::exit(2);
+}
+
+fn lookup(args: &Args) -> Result<Output, String> {
+    args.check_options(&[])?;
+    if args.positional.is_empty() {
+        return Err("lookup requires at least one zipcode".to_string());
+    }
+    let mut zipcodes = Vec::new();
+    for zipcode in &args.positional {
+        zipcodes.extend(zipcodes::matching(zipcode.as_str(), None).map_err(|e| format!("{}: {}", zipcode, e))?);
+    }
+    Ok(Output::zipcodes(&zipcodes))
+}
+
+fn validate(args: &Args) -> Result<Output, String> {
+    args.check_options(&[])?;
+    let stdin = io::stdin();
+    let mut rows = Vec::new();
+    for line in stdin.lock().lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        let input = line.trim();
+        if input.is_empty() {
+            continue;
+        }
+        let (status, message) = match zipcodes::is_real(input) {
+            Ok(true) => continue,
+            Ok(false) => ("unknown", "zipcode does not exist in the database".to_string()),
+            Err(e) => ("invalid", e.to_string()),
+        };
+        rows.push(json!({ "input": input, "status": status, "message": message }));
+    }
+    Ok(Output { columns: vec!["input", "status", "message"], failed: !rows.is_empty(), rows })
+}
+
+fn near(args: &Args) -> Result<Output, String> {
+    args.check_options(&["radius", "unit"])?;
+    let center = match args.positional.as_slice() {
+        [center] => center,
+        _ => return Err("near requires exactly one zipcode".to_string()),
+    };
+    let radius = args.parsed_option::<f64>("radius")?.unwrap_or(10.0);
+    let unit = match args.option("unit").map(|u| u.to_ascii_lowercase()) {
+        None => Unit::Miles,
+        Some(u) if u == "mi" || u == "miles" => Unit::Miles,
+        Some(u) if u == "km" || u == "kilometers" => Unit::Kilometers,
+        Some(u) => return Err(format!("invalid --unit {:?}, expected mi or km", u)),
+    };
+    let found = zipcodes::within_radius(center.as_str(), radius, unit, None).map_err(|e| e.to_string())?;
+    let mut columns = vec!["distance"];
+    columns.extend_from_slice(ZIPCODE_COLUMNS);
+    let rows = found.into_iter()
+        .map(|(zipcode, distance)| {
+            let mut row = serde_json::to_value(zipcode).expect("zipcodes serialize to JSON");
+            row["distance"] = json!((distance * 100.0).round() / 100.0);
+            row
+        })
+        .collect();
+    Ok(Output { columns, rows, failed: false })
+}
+
+fn filter(args: &Args) -> Result<Output, String> {
+    args.check_options(&["state", "city", "county", "type", "active"])?;
+    let mut filters: Vec<Filter> = Vec::new();
+    if let Some(state) = args.parsed_option::<State>("state")? {
+        filters.push(Box::new(move |z| z.state == state));
+    }
+    if let Some(city) = args.option("city").map(str::to_string) {
+        filters.push(Box::new(move |z| z.city.eq_ignore_ascii_case(&city)));
+    }
+    if let Some(county) = args.option("county").map(str::to_string) {
+        filters.push(Box::new(move |z| z.county.eq_ignore_ascii_case(&county)));
+    }
+    if let Some(zip_code_type) = args.parsed_option::<ZipcodeType>("type")? {
+        filters.push(Box::new(move |z| z.zip_code_type == zip_code_type));
+    }
+    if let Some(active) = args.parsed_option::<bool>("active")? {
+        filters.push(Box::new(move |z| z.active == active));
+    }
+    let zipcodes = zipcodes::filter_by_ref(filters).map_err(|e| e.to_string())?;
+    Ok(Output::zipcodes(zipcodes))
+}
+
+fn export(args: &Args) -> Result<Output, String> {
+    args.check_options(&[])?;
+    let zipcodes = zipcodes::iter().map_err(|e| e.to_string())?;
+    Ok(Output::zipcodes(zipcodes))
+}
+
+fn render<W: Write>(output: &Output, format: Format, out: &mut W) -> io::Result<()> {
+    match format {
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *out, &output.rows)?;
+            writeln!(out)
+        }
+        Format::Csv => {
+            writeln!(out, "{}", output.columns.join(","))?;
+            for row in &output.rows {
+                let cells = output.columns.iter().map(|c| csv_escape(&cell(row, c))).collect::<Vec<_>>();
+                writeln!(out, "{}", cells.join(","))?;
+            }
+            Ok(())
+        }
+        Format::Table => {
+            let cells = output.rows.iter()
+                .map(|row| output.columns.iter().map(|c| cell(row, c)).collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            let widths = output.columns.iter().enumerate()
+                .map(|(i, c)| cells.iter().map(|r| r[i].chars().count()).chain(Some(c.len())).max().unwrap_or(0))
+                .collect::<Vec<_>>();
+            let line = |values: Vec<&str>| {
+                values.iter().zip(&widths)
+                    .map(|(v, w)| format!("{:<width$}", v, width = w))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+                    .trim_end()
+                    .to_string()
+            };
+            writeln!(out, "{}", line(output.columns.clone()))?;
+            for row in &cells {
+                writeln!(out, "{}", line(row.iter().map(String::as_str).collect()))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// The plain-text form of one field of a row, with lists joined by ";".
+fn cell(row: &Value, column: &str) -> String {
+    match &row[column] {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(values) => values.iter().map(|v| cell(&json!({ "v": v }), "v")).collect::<Vec<_>>().join(";"),
+        value => value.to_string(),
+    }
+}
+
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Args {
+        Args::parse(line.split_whitespace().map(str::to_string)).unwrap()
+    }
+
+    #[test]
+    fn should_parse_commands_and_options() {
+        let parsed = args("near 77429 --radius 25 --format=csv");
+        assert_eq!(parsed.command, "near");
+        assert_eq!(parsed.positional, ["77429"]);
+        assert_eq!(parsed.parsed_option::<f64>("radius").unwrap(), Some(25.0));
+        assert_eq!(parsed.parsed_option::<Format>("format").unwrap(), Some(Format::Csv));
+        assert!(parsed.check_options(&["radius"]).is_ok());
+        assert!(args("lookup 77429 --radius 1").check_options(&[]).is_err());
+        assert!(Args::parse(vec!["filter".to_string(), "--state".to_string()]).is_err());
+        assert_eq!(args("--help").command, "help");
+        assert_eq!(args("near 77429 -h --radius").command, "help");
+    }
+
+    #[cfg(any(feature = "bzip2", feature = "static-data"))]
+    #[test]
+    fn should_render_every_format() {
+        let output = filter(&args("filter --state TX --city cypress --type STANDARD")).unwrap();
+        assert!(output.rows.iter().any(|r| r["zip_code"] == "77429"));
+
+        let mut csv = Vec::new();
+        render(&output, Format::Csv, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("zip_code,zip_code_type,active,city,state"));
+        assert!(csv.contains("77429,STANDARD,true,Cypress,TX,Harris County,29.9857,-95.6548,America/Chicago,281;832"));
+
+        let mut table = Vec::new();
+        render(&output, Format::Table, &mut table).unwrap();
+        assert!(String::from_utf8(table).unwrap().lines().all(|l| !l.ends_with(' ')));
+
+        let mut json = Vec::new();
+        render(&output, Format::Json, &mut json).unwrap();
+        assert_eq!(serde_json::from_slice::<Value>(&json).unwrap(), Value::Array(output.rows));
+    }
+
+    #[cfg(any(feature = "bzip2", feature = "static-data"))]
+    #[test]
+    fn should_report_nearby_zipcodes_with_distances() {
+        let output = near(&args("near 77429 --radius 5 --unit km")).unwrap();
+        assert_eq!(output.rows[0]["zip_code"], "77429");
+        assert_eq!(output.rows[0]["distance"], 0.0);
+        assert!(near(&args("near 77429 --unit furlongs")).is_err());
+    }
+}
@@ -0,0 +1,285 @@
+//! A dense binary encoding of zipcode records, for targets where neither the size of the JSON
+//! dataset nor a bzip2 decoder is affordable, such as the WebAssembly bindings in `wasm/`.
+//!
+//! Every string is stored once in a table and referenced by index, zipcodes are stored as
+//! deltas from the previous record, and coordinates that round-trip through a fixed-point
+//! decimal are stored as one. All integers are LEB128 varints, with signed values zigzagged.
+
+use std::collections::HashMap;
+
+use crate::{Error, Result, State, WorldRegion, Zipcode, ZipcodeType};
+
+const MAGIC: &[u8] = b"ZIPC\x01";
+
+/// The scale byte marking a coordinate stored as a string reference rather than a decimal.
+const STRING_COORDINATE: u8 = u8::MAX;
+
+/// Encode records sorted by `zip_code`, each of which must be exactly five digits.
+pub(crate) fn encode(zipcodes: &[Zipcode]) -> Result<Vec<u8>> {
+    let mut strings = Strings::default();
+    let mut records = Vec::new();
+    let mut previous = 0;
+    for zipcode in zipcodes {
+        let zip_code = zip_code_value(&zipcode.zip_code)?;
+        let delta = zip_code.checked_sub(previous).ok_or(Error::InvalidFormat)?;
+        previous = zip_code;
+        write_varint(&mut records, u64::from(delta));
+        records.push(u8::from(zipcode.active) | (position(ZipcodeType::ALL, &zipcode.zip_code_type) << 1));
+        records.push(position(State::ALL, &zipcode.state));
+        records.push(position(WorldRegion::ALL, &zipcode.world_region));
+        for field in [&zipcode.city, &zipcode.county, &zipcode.country, &zipcode.timezone] {
+            write_varint(&mut records, strings.intern(field));
+        }
+        for coordinate in [&zipcode.lat, &zipcode.long] {
+            match to_decimal(coordinate) {
+                Some((mantissa, scale)) => {
+                    records.push(scale);
+                    write_varint(&mut records, zigzag(mantissa));
+                }
+                None => {
+                    records.push(STRING_COORDINATE);
+                    write_varint(&mut records, strings.intern(coordinate));
+                }
+            }
+        }
+        for list in [&zipcode.area_codes, &zipcode.acceptable_cities, &zipcode.unacceptable_cities] {
+            write_varint(&mut records, list.len() as u64);
+            for s in list {
+                write_varint(&mut records, strings.intern(s));
+            }
+        }
+    }
+
+    let mut bytes = MAGIC.to_vec();
+    write_varint(&mut bytes, strings.table.len() as u64);
+    for s in &strings.table {
+        write_varint(&mut bytes, s.len() as u64);
+        bytes.extend_from_slice(s.as_bytes());
+    }
+    write_varint(&mut bytes, zipcodes.len() as u64);
+    bytes.extend_from_slice(&records);
+    Ok(bytes)
+}
+
+/// Decode records written by `encode`.
+pub(crate) fn decode(bytes: &[u8]) -> Result<Vec<Zipcode>> {
+    let bytes = bytes.strip_prefix(MAGIC).ok_or_else(|| invalid("missing header"))?;
+    let mut reader = Reader { bytes };
+    let table = (0..reader.varint()?)
+        .map(|_| {
+            let len = reader.varint()? as usize;
+            Ok(std::str::from_utf8(reader.take(len)?)?.to_string())
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let string = |reader: &mut Reader<'_>| -> Result<String> {
+        let index = reader.varint()? as usize;
+        table.get(index).cloned().ok_or_else(|| invalid("string reference out of bounds"))
+    };
+    let list = |reader: &mut Reader<'_>| -> Result<Vec<String>> {
+        (0..reader.varint()?).map(|_| string(reader)).collect()
+    };
+
+    let count = reader.varint()? as usize;
+    let mut zipcodes = Vec::with_capacity(count.min(bytes.len()));
+    let mut zip_code = 0u64;
+    for _ in 0..count {
+        zip_code = zip_code.checked_add(reader.varint()?).ok_or_else(|| invalid("zipcode out of range"))?;
+        if zip_code > 99_999 {
+            return Err(invalid("zipcode out of range"));
+        }
+        let flags = reader.byte()?;
+        let zip_code_type = variant(ZipcodeType::ALL, flags >> 1)?;
+        let state = variant(State::ALL, reader.byte()?)?;
+        let world_region = variant(WorldRegion::ALL, reader.byte()?)?;
+        let (city, county, country, timezone) = (string(&mut reader)?, string(&mut reader)?, string(&mut reader)?, string(&mut reader)?);
+        let mut coordinates = [String::new(), String::new()];
+        for coordinate in coordinates.iter_mut() {
+            *coordinate = match reader.byte()? {
+                STRING_COORDINATE => string(&mut reader)?,
+                scale => from_decimal(unzigzag(reader.varint()?), scale),
+            };
+        }
+        let [lat, long] = coordinates;
+        zipcodes.push(Zipcode {
+            area_codes: list(&mut reader)?,
+            acceptable_cities: list(&mut reader)?,
+            unacceptable_cities: list(&mut reader)?,
+            active: flags & 1 == 1,
+            city,
+            country,
+            county,
+            lat,
+            long,
+            state,
+            timezone,
+            world_region,
+            zip_code: format!("{:05}", zip_code),
+            zip_code_type,
+        });
+    }
+    if !reader.bytes.is_empty() {
+        return Err(invalid("trailing bytes"));
+    }
+    Ok(zipcodes)
+}
+
+#[derive(Default)]
+struct Strings<'a> {
+    table: Vec<&'a str>,
+    indexes: HashMap<&'a str, u64>,
+}
+
+impl<'a> Strings<'a> {
+    fn intern(&mut self, s: &'a str) -> u64 {
+        let table = &mut self.table;
+        *self.indexes.entry(s).or_insert_with(|| {
+            table.push(s);
+            table.len() as u64 - 1
+        })
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.bytes.len() {
+            return Err(invalid("unexpected end of data"));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            if shift == 63 && byte > 1 {
+                break;
+            }
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid("varint overflow"))
+    }
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::InvalidCompact(reason.to_string())
+}
+
+fn zip_code_value(zip_code: &str) -> Result<u32> {
+    if zip_code.len() != 5 || !zip_code.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(Error::InvalidFormat);
+    }
+    Ok(zip_code.parse().expect("five digits"))
+}
+
+fn position<T: PartialEq>(all: &[T], value: &T) -> u8 {
+    all.iter().position(|v| v == value).expect("every variant is in ALL") as u8
+}
+
+fn variant<T: Copy>(all: &[T], index: u8) -> Result<T> {
+    all.get(usize::from(index)).copied().ok_or_else(|| invalid("unknown variant"))
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+/// Split a decimal string like "-95.6548" into a mantissa and a number of fractional digits, if
+/// formatting them back gives exactly the same string.
+fn to_decimal(s: &str) -> Option<(i64, u8)> {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if fraction.len() >= usize::from(STRING_COORDINATE) || whole.len() + fraction.len() > 18 {
+        return None;
+    }
+    let mut mantissa = format!("{}{}", whole, fraction).parse::<i64>().ok()?;
+    if s.starts_with('-') {
+        mantissa = -mantissa;
+    }
+    let scale = fraction.len() as u8;
+    (from_decimal(mantissa, scale) == s).then_some((mantissa, scale))
+}
+
+fn from_decimal(mantissa: i64, scale: u8) -> String {
+    let scale = usize::from(scale);
+    let digits = format!("{:0width$}", mantissa.unsigned_abs(), width = scale + 1);
+    let sign = if mantissa < 0 { "-" } else { "" };
+    match scale {
+        0 => format!("{}{}", sign, digits),
+        _ => format!("{}{}.{}", sign, &digits[..digits.len() - scale], &digits[digits.len() - scale..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_round_trip_coordinates() {
+        for s in ["29.9857", "-95.6548", "-74", "0.0000", "-0.5", "40.71"] {
+            let (mantissa, scale) = to_decimal(s).unwrap();
+            assert_eq!(from_decimal(mantissa, scale), s);
+        }
+        for s in ["", "-0", ".5", "1e5", "+1", "01.5"] {
+            assert_eq!(to_decimal(s), None, "{:?}", s);
+        }
+        assert_eq!(unzigzag(zigzag(-407100)), -407100);
+    }
+
+    #[test]
+    fn should_reject_overflowing_input() {
+        // A table holding only the empty string, then two records whose zipcode deltas sum past
+        // `u64::MAX`, with every other field referencing the empty string.
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&[1, 0, 2]);
+        for delta in [1, u64::MAX] {
+            write_varint(&mut bytes, delta);
+            bytes.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, STRING_COORDINATE, 0, STRING_COORDINATE, 0, 0, 0, 0]);
+        }
+        assert!(matches!(decode(&bytes), Err(Error::InvalidCompact(reason)) if reason == "zipcode out of range"));
+        let valid = bytes.len() - 24;
+        assert_eq!(decode(&[&bytes[..MAGIC.len() + 2], &[1], &bytes[MAGIC.len() + 3..valid]].concat()).unwrap().len(), 1);
+
+        let mut reader = Reader { bytes: &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02] };
+        assert!(matches!(reader.varint(), Err(Error::InvalidCompact(_))));
+        let mut reader = Reader { bytes: &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01] };
+        assert_eq!(reader.varint().unwrap(), u64::MAX);
+    }
+
+    #[cfg(any(feature = "bzip2", feature = "static-data"))]
+    #[test]
+    fn should_round_trip_the_embedded_dataset() {
+        let zipcodes = crate::try_load().unwrap().zipcodes();
+        let bytes = encode(zipcodes).unwrap();
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(decoded, zipcodes);
+        assert!(bytes.len() < 2_000_000, "{} bytes", bytes.len());
+
+        assert!(matches!(decode(&bytes[..bytes.len() - 1]), Err(Error::InvalidCompact(_))));
+        assert!(matches!(decode(b"[]"), Err(Error::InvalidCompact(_))));
+    }
+}
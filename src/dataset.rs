@@ -0,0 +1,254 @@
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::mem;
+use std::path::Path;
+
+use crate::{Error, Result, Zip5, Zipcode, ZipcodeDb, ZipcodeType};
+
+/// The columns read from a source file, as named in the header of the zip code database CSV
+/// published by unitedstateszipcodes.org, which the dataset is built from. Other columns, such as
+/// `irs_estimated_population`, are ignored.
+const COLUMNS: &[&str] = &[
+    "zip", "type", "decommissioned", "primary_city", "acceptable_cities", "unacceptable_cities", "state", "county",
+    "timezone", "area_codes", "world_region", "country", "latitude", "longitude",
+];
+
+/// Builds a zipcode dataset from raw source files, validating and de-duplicating their records.
+///
+/// Sources are read in priority order: a row identical to one already read is dropped as a
+/// duplicate, and a different row for a zipcode already read is dropped as a conflict. The
+/// resulting database can be written out with `ZipcodeDb::to_writer`, for example as the
+/// `Format::Bzip2Json` embedded in the crate.
+#[derive(Debug, Default)]
+pub struct DatasetBuilder {
+    zipcodes: Vec<Zipcode>,
+    positions: HashMap<String, usize>,
+    stats: DatasetStats,
+}
+
+/// What a `DatasetBuilder` read and kept.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DatasetStats {
+    /// The number of data rows read across every source.
+    pub rows: usize,
+    /// The number of records kept.
+    pub records: usize,
+    /// The number of rows dropped for repeating a record exactly.
+    pub duplicates: usize,
+    /// The zipcodes of rows dropped for disagreeing with an earlier record, once per row.
+    pub conflicts: Vec<String>,
+    /// The number of records for decommissioned zipcodes.
+    pub inactive: usize,
+    /// The number of records without usable coordinates.
+    pub missing_coordinates: usize,
+    /// The number of records of each type.
+    pub by_type: BTreeMap<ZipcodeType, usize>,
+}
+
+impl DatasetBuilder {
+    /// Create a builder with no sources.
+    pub fn new() -> Self {
+        DatasetBuilder::default()
+    }
+
+    /// Read a source CSV from a reader. A row that cannot be turned into a valid record fails
+    /// with `Error::InvalidRecord`, carrying the line it starts on.
+    pub fn read_csv<R: Read>(&mut self, mut reader: R) -> Result<&mut Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let text = std::str::from_utf8(&bytes)?;
+        let mut rows = parse_csv(text.strip_prefix('\u{feff}').unwrap_or(text))?.into_iter();
+        let header = match rows.next() {
+            Some((_, header)) => header,
+            None => return Ok(self),
+        };
+        let columns = COLUMNS.iter()
+            .map(|name| {
+                header.iter()
+                    .position(|h| h.trim() == *name)
+                    .ok_or_else(|| Error::InvalidRecord { line: 1, reason: format!("missing column {:?}", name) })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for (line, row) in rows {
+            let field = |column: usize| row.get(columns[column]).map_or("", |f| f.trim());
+            let zipcode = to_zipcode(field).map_err(|e| Error::InvalidRecord { line, reason: e.to_string() })?;
+            self.stats.rows += 1;
+            match self.positions.entry(zipcode.zip_code.clone()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(self.zipcodes.len());
+                    self.zipcodes.push(zipcode);
+                }
+                Entry::Occupied(entry) if self.zipcodes[*entry.get()] == zipcode => self.stats.duplicates += 1,
+                Entry::Occupied(_) => self.stats.conflicts.push(zipcode.zip_code),
+            }
+        }
+        Ok(self)
+    }
+
+    /// Read a source CSV from a file, as in `read_csv`.
+    pub fn read_csv_path<P: AsRef<Path>>(&mut self, path: P) -> Result<&mut Self> {
+        self.read_csv(File::open(path)?)
+    }
+
+    /// Finish the dataset, returning it along with what was read and kept.
+    pub fn build(self) -> (ZipcodeDb, DatasetStats) {
+        let mut stats = self.stats;
+        stats.records = self.zipcodes.len();
+        for zipcode in &self.zipcodes {
+            stats.inactive += usize::from(!zipcode.active);
+            stats.missing_coordinates += usize::from(zipcode.coordinates().is_err());
+            *stats.by_type.entry(zipcode.zip_code_type).or_default() += 1;
+        }
+        (ZipcodeDb::from_zipcodes(self.zipcodes), stats)
+    }
+}
+
+impl fmt::Display for DatasetStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "rows read: {}", self.rows)?;
+        writeln!(f, "records kept: {}", self.records)?;
+        writeln!(f, "duplicate rows dropped: {}", self.duplicates)?;
+        write!(f, "conflicting rows dropped: {}", self.conflicts.len())?;
+        if !self.conflicts.is_empty() {
+            write!(f, " ({})", self.conflicts.join(", "))?;
+        }
+        writeln!(f)?;
+        writeln!(f, "inactive records: {}", self.inactive)?;
+        write!(f, "records missing coordinates: {}", self.missing_coordinates)?;
+        for (zip_code_type, count) in &self.by_type {
+            write!(f, "\n{} records: {}", zip_code_type, count)?;
+        }
+        Ok(())
+    }
+}
+
+/// Build a record from the fields of a row, looked up by their position in `COLUMNS`.
+fn to_zipcode<'a, F: Fn(usize) -> &'a str>(field: F) -> Result<Zipcode> {
+    let zip_code = field(0);
+    if zip_code.len() != 5 {
+        return Err(Error::InvalidFormat);
+    }
+    let active = match field(2) {
+        "0" | "" => true,
+        "1" => false,
+        other => return Err(Error::UnknownVariant { kind: "decommissioned", value: other.to_string() }),
+    };
+    let list = |column: usize| field(column).split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect();
+    let coordinate = |column: usize| match field(column) {
+        "" => "0".to_string(),
+        value => value.to_string(),
+    };
+    Ok(Zipcode {
+        acceptable_cities: list(4),
+        active,
+        area_codes: list(9),
+        city: field(3).to_string(),
+        country: field(11).to_string(),
+        county: field(7).to_string(),
+        lat: coordinate(12),
+        long: coordinate(13),
+        state: field(6).parse()?,
+        timezone: field(8).to_string(),
+        unacceptable_cities: list(5),
+        world_region: field(10).parse()?,
+        zip_code: zip_code.parse::<Zip5>()?.to_string(),
+        zip_code_type: field(1).parse()?,
+    })
+}
+
+/// Split CSV text into rows of fields, each with the line it starts on. Fields may be quoted, in
+/// which case they may contain commas, line breaks and doubled quotes. Blank lines are skipped.
+fn parse_csv(text: &str) -> Result<Vec<(usize, Vec<String>)>> {
+    let mut rows = Vec::new();
+    let (mut row, mut field) = (Vec::new(), String::new());
+    let (mut line, mut start, mut quoted) = (1, 1, false);
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => row.push(mem::take(&mut field)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' if !quoted => {
+                row.push(mem::take(&mut field));
+                if row.iter().any(|f| !f.is_empty()) {
+                    rows.push((start, mem::take(&mut row)));
+                }
+                row.clear();
+                line += 1;
+                start = line;
+            }
+            _ => {
+                line += usize::from(c == '\n');
+                field.push(c);
+            }
+        }
+    }
+    if quoted {
+        return Err(Error::InvalidRecord { line: start, reason: "unterminated quoted field".to_string() });
+    }
+    row.push(field);
+    if row.iter().any(|f| !f.is_empty()) {
+        rows.push((start, row));
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::State;
+
+    const HEADER: &str = "zip,type,decommissioned,primary_city,acceptable_cities,unacceptable_cities,state,county,timezone,area_codes,world_region,country,latitude,longitude,irs_estimated_population\n";
+
+    #[test]
+    fn should_build_a_deduplicated_dataset() {
+        let first = format!(
+            "{}{}{}{}",
+            HEADER,
+            "06475,STANDARD,0,Old Saybrook,,Fenwick,CT,Middlesex County,America/New_York,860,NA,US,41.3015,-72.3879,9640\r\n",
+            "27983,STANDARD,0,Windsor,Askewville,,NC,Bertie County,America/New_York,252,NA,US,35.9942,-76.9422,5270\n\n",
+            "\"77429\",STANDARD,0,Cypress,,\"Cypress, Tx\",TX,Harris County,America/Chicago,\"281,832\",NA,US,29.9857,-95.6548,\"81,000\"\n",
+        );
+        let second = format!(
+            "{}{}{}",
+            HEADER,
+            "06475,STANDARD,0,Old Saybrook,,Fenwick,CT,Middlesex County,America/New_York,860,NA,US,41.3015,-72.3879,9640\n",
+            "77429,PO BOX,1,Cypress,,,TX,Harris County,America/Chicago,281,NA,US,,,0",
+        );
+        let mut builder = DatasetBuilder::new();
+        builder.read_csv(first.as_bytes()).unwrap().read_csv(second.as_bytes()).unwrap();
+        let (db, stats) = builder.build();
+
+        assert_eq!(db.list_all().iter().map(|z| z.zip_code.as_str()).collect::<Vec<_>>(), ["06475", "27983", "77429"]);
+        let cypress = db.get("77429").unwrap().unwrap();
+        assert_eq!(cypress.area_codes, ["281", "832"]);
+        assert_eq!(cypress.unacceptable_cities, ["Cypress", "Tx"]);
+        assert_eq!((cypress.state, cypress.active), (State::Texas, true));
+        assert_eq!(db.get("27983").unwrap().unwrap().acceptable_cities, ["Askewville"]);
+
+        assert_eq!((stats.rows, stats.records, stats.duplicates), (5, 3, 1));
+        assert_eq!(stats.conflicts, ["77429"]);
+        assert_eq!(stats.by_type[&ZipcodeType::Standard], 3);
+        assert!(stats.to_string().contains("conflicting rows dropped: 1 (77429)"));
+    }
+
+    #[test]
+    fn should_report_invalid_rows() {
+        let invalid = |rows: &str| DatasetBuilder::new().read_csv(format!("{}{}", HEADER, rows).as_bytes()).map(|_| ()).unwrap_err();
+        let row = "06475,STANDARD,0,Old Saybrook,,,CT,,,,NA,US,41.3015,-72.3879,0\n";
+        assert!(matches!(invalid(&format!("{}{}", row, row.replace("CT", "XX"))), Error::InvalidRecord { line: 3, .. }));
+        assert!(matches!(invalid(&row.replace("06475", "0647")), Error::InvalidRecord { line: 2, .. }));
+        assert!(matches!(invalid(&row.replace(",0,", ",yes,")), Error::InvalidRecord { line: 2, .. }));
+        assert!(matches!(invalid("\"06475,STANDARD\n"), Error::InvalidRecord { line: 2, .. }));
+        assert!(matches!(DatasetBuilder::new().read_csv("zip,type\n".as_bytes()), Err(Error::InvalidRecord { line: 1, .. })));
+    }
+}
@@ -0,0 +1,460 @@
+#[cfg(feature = "bzip2")]
+use bzip2::{read::BzDecoder, write::BzEncoder, Compression};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::de::{DeserializeSeed, SeqAccess, Visitor};
+use std::cell::Cell;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::autocomplete::AutocompleteIndex;
+use crate::compact;
+use crate::phone;
+use crate::spatial::{to_point, KdTree};
+use crate::{clean_prefix, Error, Result, State, ToZip5, Zip5, Zipcode};
+
+#[cfg(all(feature = "bzip2", not(feature = "static-data")))]
+static ZIPCODE_BYTES_BZIP: &[u8] = include_bytes!("zips.json.bz2");
+
+/// The embedded database, or the reason it could not be loaded. The error is shared so that
+/// every caller after the first can still be told why.
+#[cfg(all(feature = "bzip2", not(feature = "static-data")))]
+static EMBEDDED: Lazy<std::result::Result<ZipcodeDb, Arc<Error>>> = Lazy::new(|| {
+    ZipcodeDb::from_reader(ZIPCODE_BYTES_BZIP, Format::Bzip2Json).map_err(Arc::new)
+});
+
+/// With the `static-data` feature, the records are copied out of the table compiled in by the
+/// build script, so loading involves no decompression or parsing, but still allocates every
+/// record. `is_real`, `matching` and `similar_to` read the table directly instead of loading this.
+#[cfg(feature = "static-data")]
+static EMBEDDED: Lazy<std::result::Result<ZipcodeDb, Arc<Error>>> = Lazy::new(|| {
+    Ok(ZipcodeDb::from_zipcodes(crate::static_data::all().map(Zipcode::from).collect()))
+});
+
+/// Without the `bzip2` feature there is no embedded dataset, only databases loaded at runtime.
+#[cfg(not(any(feature = "bzip2", feature = "static-data")))]
+static EMBEDDED: Lazy<std::result::Result<ZipcodeDb, Arc<Error>>> = Lazy::new(|| {
+    Err(Arc::new(Error::Unsupported(Format::Bzip2Json)))
+});
+
+/// Load and index the embedded zipcode database, returning it once it is ready.
+///
+/// Every lookup against the embedded data does this lazily on first use, so calling it is
+/// optional; doing so at startup moves the cost out of the first query and surfaces a corrupt
+/// dataset as an error up front. Later calls are cheap and return the same result.
+pub fn try_load() -> Result<&'static ZipcodeDb> {
+    EMBEDDED.as_ref().map_err(|e| Error::Embedded(e.clone()))
+}
+
+/// Load and index the embedded zipcode database, as in `try_load`.
+pub fn init() -> Result<()> {
+    try_load().map(|_| ())
+}
+
+/// The encoding of a zipcode dataset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// A single JSON array of zipcode records.
+    Json,
+    /// One JSON zipcode record per line. Blank lines are skipped.
+    Ndjson,
+    /// A bzip2-compressed JSON array of zipcode records, like the embedded dataset. Requires the
+    /// `bzip2` feature.
+    Bzip2Json,
+    /// The crate's own dense binary encoding, which is several times smaller than JSON and needs
+    /// no decompressor to read. Records must have 5-digit zipcodes to be written in it.
+    Compact,
+}
+
+impl Format {
+    /// Guess the format of a file from its extension: `.bz2` is `Bzip2Json`, `.ndjson` and
+    /// `.jsonl` are `Ndjson`, `.bin` is `Compact`, and anything else is `Json`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Format {
+        match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some(e) if e.eq_ignore_ascii_case("bz2") => Format::Bzip2Json,
+            Some(e) if e.eq_ignore_ascii_case("ndjson") || e.eq_ignore_ascii_case("jsonl") => Format::Ndjson,
+            Some(e) if e.eq_ignore_ascii_case("bin") => Format::Compact,
+            _ => Format::Json,
+        }
+    }
+}
+
+/// A queryable set of zipcodes, with the same lookups as the crate's free functions.
+///
+/// The free functions all query the dataset embedded in the crate, which is also available as
+/// `ZipcodeDb::embedded()`. A `ZipcodeDb` can instead be loaded at runtime from a newer dataset
+/// in any of the supported `Format`s. Records are kept sorted by `zip_code`.
+pub struct ZipcodeDb {
+    zipcodes: Vec<Zipcode>,
+    index: HashMap<String, Range<usize>>,
+    spatial: OnceCell<KdTree<usize>>,
+    autocomplete: OnceCell<AutocompleteIndex>,
+    area_codes: OnceCell<HashMap<String, Vec<usize>>>,
+}
+
+impl ZipcodeDb {
+    /// The dataset embedded in the crate, decompressed and indexed on first use, as in
+    /// `crate::try_load`.
+    pub fn embedded() -> Result<&'static ZipcodeDb> {
+        try_load()
+    }
+
+    /// Build a database from an in-memory list of zipcodes.
+    pub fn from_zipcodes(mut zipcodes: Vec<Zipcode>) -> Self {
+        zipcodes.sort_by(|a, b| a.zip_code.cmp(&b.zip_code));
+        let mut index: HashMap<String, Range<usize>> = HashMap::with_capacity(zipcodes.len());
+        let mut start = 0;
+        while start < zipcodes.len() {
+            let zip_code = &zipcodes[start].zip_code;
+            let end = start + zipcodes[start..].partition_point(|z| z.zip_code == *zip_code);
+            index.insert(zip_code.clone(), start..end);
+            start = end;
+        }
+        ZipcodeDb { zipcodes, index, spatial: OnceCell::new(), autocomplete: OnceCell::new(), area_codes: OnceCell::new() }
+    }
+
+    /// Load a database from a reader producing the supplied format.
+    ///
+    /// A record that does not match the `Zipcode` schema fails with `Error::Schema`, carrying the
+    /// position of the offending record.
+    pub fn from_reader<R: Read>(mut reader: R, format: Format) -> Result<Self> {
+        let mut bytes = Vec::new();
+        match format {
+            #[cfg(feature = "bzip2")]
+            Format::Bzip2Json => BzDecoder::new(reader).read_to_end(&mut bytes).map_err(Error::Decompress)?,
+            #[cfg(not(feature = "bzip2"))]
+            Format::Bzip2Json => return Err(Error::Unsupported(format)),
+            Format::Json | Format::Ndjson | Format::Compact => reader.read_to_end(&mut bytes)?,
+        };
+        let zipcodes = match format {
+            Format::Json | Format::Bzip2Json => parse_json(std::str::from_utf8(&bytes)?)?,
+            Format::Ndjson => parse_ndjson(std::str::from_utf8(&bytes)?)?,
+            Format::Compact => compact::decode(&bytes)?,
+        };
+        Ok(ZipcodeDb::from_zipcodes(zipcodes))
+    }
+
+    /// Write every record, sorted by `zip_code`, to a writer in the supplied format, which
+    /// `from_reader` reads back.
+    pub fn to_writer<W: Write>(&self, mut writer: W, format: Format) -> Result<()> {
+        match format {
+            Format::Json => serde_json::to_writer(writer, &self.zipcodes)?,
+            Format::Ndjson => {
+                for zipcode in &self.zipcodes {
+                    serde_json::to_writer(&mut writer, zipcode)?;
+                    writer.write_all(b"\n")?;
+                }
+            }
+            #[cfg(feature = "bzip2")]
+            Format::Bzip2Json => {
+                let mut encoder = BzEncoder::new(writer, Compression::best());
+                serde_json::to_writer(&mut encoder, &self.zipcodes)?;
+                encoder.finish()?;
+            }
+            #[cfg(not(feature = "bzip2"))]
+            Format::Bzip2Json => return Err(Error::Unsupported(format)),
+            Format::Compact => writer.write_all(&compact::encode(&self.zipcodes)?)?,
+        }
+        Ok(())
+    }
+
+    /// Load a database from a file, guessing its format from the extension as described on
+    /// `Format::from_path`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let format = Format::from_path(&path);
+        ZipcodeDb::from_reader(File::open(path)?, format)
+    }
+
+    /// The number of zipcode records in the database.
+    pub fn len(&self) -> usize {
+        self.zipcodes.len()
+    }
+
+    /// Whether the database contains no zipcode records.
+    pub fn is_empty(&self) -> bool {
+        self.zipcodes.is_empty()
+    }
+
+    /// Determine whether a supplied zipcode matches any existing zipcode, as in `crate::matching`.
+    pub fn matching<Z: ToZip5>(&self, zipcode: Z) -> Result<Vec<Zipcode>> {
+        Ok(self.matching_ref(zipcode)?.to_vec())
+    }
+
+    /// Returns true if the supplied zipcode is a valid zipcode, as in `crate::is_real`.
+    pub fn is_real<Z: ToZip5>(&self, zipcode: Z) -> Result<bool> {
+        Ok(!self.matching_ref(zipcode)?.is_empty())
+    }
+
+    /// Borrow the first zipcode matching the supplied zipcode, as in `crate::get`.
+    pub fn get<Z: ToZip5>(&self, zipcode: Z) -> Result<Option<&Zipcode>> {
+        Ok(self.matching_ref(zipcode)?.first())
+    }
+
+    /// Borrow every zipcode matching the supplied zipcode, as in `crate::matching_ref`.
+    pub fn matching_ref<Z: ToZip5>(&self, zipcode: Z) -> Result<&[Zipcode]> {
+        Ok(self.lookup(zipcode.to_zip5()?))
+    }
+
+    /// Return the zipcodes beginning with the supplied prefix, as in `crate::similar_to`.
+    pub fn similar_to(&self, prefix: &str) -> Result<Vec<Zipcode>> {
+        Ok(self.similar_to_ref(prefix)?.to_vec())
+    }
+
+    /// Borrow the zipcodes beginning with the supplied prefix, as in `crate::similar_to_ref`.
+    pub fn similar_to_ref(&self, prefix: &str) -> Result<&[Zipcode]> {
+        Ok(self.prefix_range(clean_prefix(prefix)?))
+    }
+
+    /// Using a supplied list of filter functions, return a filtered list of zipcodes, as in
+    /// `crate::filter_by`.
+    pub fn filter_by<F>(&self, filters: Vec<F>) -> Result<Vec<Zipcode>>
+                        where F: Fn(&Zipcode) -> bool {
+        Ok(self.filter_by_ref(filters).cloned().collect::<Vec<_>>())
+    }
+
+    /// Iterate over the zipcodes passing every supplied filter function, without cloning them, as
+    /// in `crate::filter_by_ref`.
+    pub fn filter_by_ref<'a, 'f, F>(&'a self, filters: Vec<F>) -> impl Iterator<Item = &'a Zipcode> + 'f
+                                    where 'a: 'f, F: Fn(&Zipcode) -> bool + 'f {
+        self.zipcodes.iter().filter(move |z| filters.iter().all(|f| f(z)))
+    }
+
+    /// Return the zipcodes located in the supplied county, as in `crate::by_county`.
+    pub fn by_county(&self, county: &str, state: State) -> Vec<Zipcode> {
+        let county = county.trim();
+        self.zipcodes.iter()
+            .filter(|z| z.state == state && z.county.eq_ignore_ascii_case(county))
+            .cloned()
+            .collect::<Vec<_>>()
+    }
+
+    /// List the distinct county names in the supplied state, as in `crate::counties`.
+    pub fn counties(&self, state: State) -> Vec<&str> {
+        self.zipcodes.iter()
+            .filter(|z| z.state == state && !z.county.is_empty())
+            .map(|z| z.county.as_str())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>()
+    }
+
+    /// Retrieve a list of all zipcodes in the database. This clones every record; use `iter` to
+    /// borrow them instead.
+    pub fn list_all(&self) -> Vec<Zipcode> {
+        self.zipcodes.clone()
+    }
+
+    /// Iterate over every zipcode in the database, sorted by `zip_code`, without cloning them.
+    pub fn iter(&self) -> std::slice::Iter<'_, Zipcode> {
+        self.zipcodes.iter()
+    }
+
+    pub(crate) fn zipcodes(&self) -> &[Zipcode] {
+        &self.zipcodes
+    }
+
+    pub(crate) fn lookup(&self, zipcode: Zip5) -> &[Zipcode] {
+        self.index.get(zipcode.as_str()).map(|r| &self.zipcodes[r.clone()]).unwrap_or(&[])
+    }
+
+    /// The contiguous run of zipcodes beginning with an already validated prefix.
+    pub(crate) fn prefix_range(&self, prefix: &str) -> &[Zipcode] {
+        let start = self.zipcodes.partition_point(|z| z.zip_code.as_str() < prefix);
+        let len = self.zipcodes[start..].partition_point(|z| z.zip_code.starts_with(prefix));
+        &self.zipcodes[start..start + len]
+    }
+
+    /// A k-d tree over the positions of every zipcode with usable coordinates, built on first use.
+    pub(crate) fn spatial(&self) -> &KdTree<usize> {
+        self.spatial.get_or_init(|| {
+            KdTree::new(self.zipcodes.iter()
+                .enumerate()
+                .filter_map(|(i, z)| z.coordinates().ok().map(|c| (to_point(&c), i)))
+                .collect::<Vec<_>>())
+        })
+    }
+
+    /// A prefix index over the cities of the active zipcodes, built on first use.
+    pub(crate) fn autocomplete_index(&self) -> &AutocompleteIndex {
+        self.autocomplete.get_or_init(|| AutocompleteIndex::new(&self.zipcodes))
+    }
+
+    /// The positions of the zipcodes served by each area code, built on first use.
+    pub(crate) fn area_code_index(&self) -> &HashMap<String, Vec<usize>> {
+        self.area_codes.get_or_init(|| phone::index(&self.zipcodes))
+    }
+}
+
+impl<'a> IntoIterator for &'a ZipcodeDb {
+    type Item = &'a Zipcode;
+    type IntoIter = std::slice::Iter<'a, Zipcode>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl From<Vec<Zipcode>> for ZipcodeDb {
+    fn from(zipcodes: Vec<Zipcode>) -> Self {
+        ZipcodeDb::from_zipcodes(zipcodes)
+    }
+}
+
+fn parse_json(text: &str) -> Result<Vec<Zipcode>> {
+    let parsed = Cell::new(None);
+    let mut deserializer = serde_json::Deserializer::from_str(text);
+    Records { parsed: &parsed }.deserialize(&mut deserializer)
+        .and_then(|zipcodes| deserializer.end().map(|_| zipcodes))
+        .map_err(|e| match parsed.get() {
+            Some(index) if e.is_data() => Error::Schema { index, source: e },
+            _ => Error::Json(e),
+        })
+}
+
+fn parse_ndjson(text: &str) -> Result<Vec<Zipcode>> {
+    let mut zipcodes = Vec::new();
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+        let zipcode = serde_json::from_str(line).map_err(|e| match e.is_data() {
+            true => Error::Schema { index: zipcodes.len(), source: e },
+            false => Error::Json(e),
+        })?;
+        zipcodes.push(zipcode);
+    }
+    Ok(zipcodes)
+}
+
+/// Deserializes a JSON array of zipcodes while keeping count of the records parsed so far, so
+/// that a schema error can be attributed to the record that caused it. The count stays `None`
+/// until the array itself has been found.
+struct Records<'a> {
+    parsed: &'a Cell<Option<usize>>,
+}
+
+impl<'de> DeserializeSeed<'de> for Records<'_> {
+    type Value = Vec<Zipcode>;
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Records<'_> {
+    type Value = Vec<Zipcode>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a list of zipcode records")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error> {
+        let mut zipcodes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        self.parsed.set(Some(0));
+        while let Some(zipcode) = seq.next_element()? {
+            zipcodes.push(zipcode);
+            self.parsed.set(Some(zipcodes.len()));
+        }
+        Ok(zipcodes)
+    }
+}
+
+#[cfg(all(test, any(feature = "bzip2", feature = "static-data")))]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    fn sample() -> Vec<Zipcode> {
+        ZipcodeDb::embedded().unwrap().similar_to("0690").unwrap()
+    }
+
+    /// Compress JSON independently of `ZipcodeDb::to_writer`.
+    #[cfg(feature = "bzip2")]
+    fn compress(json: &[u8]) -> Vec<u8> {
+        let mut bzip2 = Vec::new();
+        bzip2::read::BzEncoder::new(json, bzip2::Compression::fast()).read_to_end(&mut bzip2).unwrap();
+        bzip2
+    }
+
+    #[test]
+    fn should_load_every_format() {
+        let json = serde_json::to_vec(&sample()).unwrap();
+        let ndjson = sample().iter()
+            .map(|z| serde_json::to_string(z).unwrap() + "\n\n")
+            .collect::<String>();
+        let inputs = [
+            (json.clone(), Format::Json),
+            (ndjson.into_bytes(), Format::Ndjson),
+            #[cfg(feature = "bzip2")]
+            (compress(&json), Format::Bzip2Json),
+        ];
+        #[cfg(not(feature = "bzip2"))]
+        assert!(matches!(ZipcodeDb::from_reader(json.as_slice(), Format::Bzip2Json), Err(Error::Unsupported(_))));
+
+        for (bytes, format) in inputs {
+            let db = ZipcodeDb::from_reader(bytes.as_slice(), format).unwrap();
+            assert_eq!(db.len(), sample().len());
+            assert!(db.is_real("06903").unwrap());
+            assert!(!db.is_real("77429").unwrap());
+        }
+    }
+
+    #[test]
+    fn should_write_every_format() {
+        let db = ZipcodeDb::from(sample());
+        let formats = [
+            Format::Json,
+            Format::Ndjson,
+            Format::Compact,
+            #[cfg(feature = "bzip2")]
+            Format::Bzip2Json,
+        ];
+        for format in formats {
+            let mut bytes = Vec::new();
+            db.to_writer(&mut bytes, format).unwrap();
+            let read = ZipcodeDb::from_reader(bytes.as_slice(), format).unwrap();
+            assert_eq!(read.zipcodes(), db.zipcodes());
+        }
+
+        let mut zipcodes = sample();
+        zipcodes[0].zip_code = "0690".to_string();
+        assert!(matches!(ZipcodeDb::from(zipcodes).to_writer(Vec::new(), Format::Compact), Err(Error::InvalidFormat)));
+        assert_eq!(Format::from_path("zips.bin"), Format::Compact);
+    }
+
+    #[test]
+    fn should_query_a_custom_database() {
+        let mut zipcodes = sample();
+        zipcodes.reverse();
+        let db = ZipcodeDb::from(zipcodes);
+        assert_eq!(db.get("06903").unwrap().unwrap().city, "Stamford");
+        assert_eq!(db.matching("06903-1234").unwrap().len(), 1);
+        assert_eq!(db.similar_to("069").unwrap().len(), db.len());
+        assert_eq!(db.list_all()[0].zip_code, sample()[0].zip_code);
+        assert!(db.filter_by(vec![|z: &Zipcode| z.city == "Stamford"]).unwrap().iter().all(|z| z.state == State::Connecticut));
+        assert_eq!(db.filter_by_ref(vec![|z: &Zipcode| z.city == "Stamford"]).count(), db.filter_by(vec![|z: &Zipcode| z.city == "Stamford"]).unwrap().len());
+        assert!((&db).into_iter().map(|z| &z.zip_code).eq(db.list_all().iter().map(|z| &z.zip_code)));
+        assert_eq!(db.counties(State::Connecticut), ["Fairfield County"]);
+    }
+
+    #[test]
+    fn should_report_malformed_datasets() {
+        let mut records = sample().iter().map(|z| serde_json::to_value(z).unwrap()).collect::<Vec<_>>();
+        records[2]["zip_code_type"] = "PO_BOX".into();
+        let json = serde_json::to_vec(&records).unwrap();
+        assert!(matches!(ZipcodeDb::from_reader(json.as_slice(), Format::Json), Err(Error::Schema { index: 2, .. })));
+        let ndjson = records.iter().map(|r| r.to_string() + "\n").collect::<String>();
+        assert!(matches!(ZipcodeDb::from_reader(ndjson.as_bytes(), Format::Ndjson), Err(Error::Schema { index: 2, .. })));
+
+        assert!(matches!(ZipcodeDb::from_reader("{}".as_bytes(), Format::Json), Err(Error::Json(_))));
+        assert!(matches!(ZipcodeDb::from_reader("[{}".as_bytes(), Format::Json), Err(Error::Schema { index: 0, .. })));
+        assert!(matches!(ZipcodeDb::from_reader("[".as_bytes(), Format::Json), Err(Error::Json(_))));
+        assert!(matches!(ZipcodeDb::from_reader(&b"[\"\xff\"]"[..], Format::Json), Err(Error::Utf8(_))));
+        #[cfg(feature = "bzip2")]
+        assert!(matches!(ZipcodeDb::from_reader("[]".as_bytes(), Format::Bzip2Json), Err(Error::Decompress(_))));
+        assert!(matches!(ZipcodeDb::from_path("does/not/exist.json"), Err(Error::Io(_))));
+        assert_eq!(Format::from_path("zips.json.bz2"), Format::Bzip2Json);
+        assert_eq!(Format::from_path("zips.ndjson"), Format::Ndjson);
+    }
+}
@@ -0,0 +1,554 @@
+//! The C API exported by the `cdylib` build of the crate.
+//!
+//! Every function reports failure through a `ZipcodesError` code and writes its result through an
+//! out-pointer. Handles returned to C are owned by the caller and must be released with the
+//! matching `*_free` function. The header at `include/zipcodes.h` is generated from this module
+//! with `cbindgen --config cbindgen.toml --output include/zipcodes.h`.
+
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, UnwindSafe};
+use std::ptr;
+
+use crate::{Error, Zipcode};
+
+/// The status returned by every function in the C API. Apart from `Ok`, `NullPointer` and
+/// `Panic`, each code corresponds to a variant of the Rust `Error` enum.
+///
+/// The values are part of the ABI and never change: new codes take the next unused value, and
+/// `Panic` is always -1.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZipcodesError {
+    Ok = 0,
+    NullPointer = 1,
+    InvalidFormat = 2,
+    InvalidCharacters = 3,
+    Empty = 4,
+    InvalidSeparator = 5,
+    MissingPlus4 = 6,
+    UnknownZipcode = 7,
+    MissingCoordinates = 8,
+    InvalidRadius = 9,
+    InvalidCoordinates = 10,
+    UnknownVariant = 11,
+    InvalidPrefixLength = 12,
+    InvalidPrefixCharacters = 13,
+    Io = 14,
+    Json = 15,
+    Decompress = 16,
+    Utf8 = 17,
+    Schema = 18,
+    Embedded = 19,
+    Unsupported = 20,
+    InvalidRecord = 21,
+    InvalidAreaCode = 22,
+    InvalidPhoneNumber = 23,
+    InvalidCompact = 24,
+    Panic = -1,
+}
+
+impl From<&Error> for ZipcodesError {
+    fn from(e: &Error) -> Self {
+        match e {
+            Error::InvalidFormat => ZipcodesError::InvalidFormat,
+            Error::InvalidCharacters => ZipcodesError::InvalidCharacters,
+            Error::Empty => ZipcodesError::Empty,
+            Error::InvalidSeparator(_) => ZipcodesError::InvalidSeparator,
+            Error::MissingPlus4 => ZipcodesError::MissingPlus4,
+            Error::UnknownZipcode(_) => ZipcodesError::UnknownZipcode,
+            Error::MissingCoordinates(_) => ZipcodesError::MissingCoordinates,
+            Error::InvalidRadius(_) => ZipcodesError::InvalidRadius,
+            Error::InvalidCoordinates(..) => ZipcodesError::InvalidCoordinates,
+            Error::UnknownVariant { .. } => ZipcodesError::UnknownVariant,
+            Error::InvalidPrefixLength => ZipcodesError::InvalidPrefixLength,
+            Error::InvalidPrefixCharacters => ZipcodesError::InvalidPrefixCharacters,
+            Error::Io(_) => ZipcodesError::Io,
+            Error::Json(_) => ZipcodesError::Json,
+            Error::Decompress(_) => ZipcodesError::Decompress,
+            Error::Utf8(_) => ZipcodesError::Utf8,
+            Error::Schema { .. } => ZipcodesError::Schema,
+            Error::Embedded(_) => ZipcodesError::Embedded,
+            Error::Unsupported(_) => ZipcodesError::Unsupported,
+            Error::InvalidRecord { .. } => ZipcodesError::InvalidRecord,
+            Error::InvalidAreaCode(_) => ZipcodesError::InvalidAreaCode,
+            Error::InvalidPhoneNumber(_) => ZipcodesError::InvalidPhoneNumber,
+            Error::InvalidCompact(_) => ZipcodesError::InvalidCompact,
+        }
+    }
+}
+
+/// An owned zipcode record, along with C copies of its fields that live as long as the handle.
+pub struct ZipcodesZipcode {
+    zip_code: CString,
+    zip_code_type: CString,
+    city: CString,
+    state: CString,
+    county: CString,
+    country: CString,
+    timezone: CString,
+    world_region: CString,
+    lat: f64,
+    long: f64,
+    active: bool,
+    area_codes: Vec<CString>,
+    acceptable_cities: Vec<CString>,
+    unacceptable_cities: Vec<CString>,
+}
+
+/// An owned list of zipcode records.
+pub struct ZipcodesList {
+    zipcodes: Vec<ZipcodesZipcode>,
+}
+
+impl ZipcodesZipcode {
+    fn new(zipcode: &Zipcode) -> Self {
+        let coordinates = zipcode.coordinates().ok();
+        ZipcodesZipcode {
+            zip_code: c_string(&zipcode.zip_code),
+            zip_code_type: c_string(zipcode.zip_code_type.as_str()),
+            city: c_string(&zipcode.city),
+            state: c_string(zipcode.state.as_str()),
+            county: c_string(&zipcode.county),
+            country: c_string(&zipcode.country),
+            timezone: c_string(&zipcode.timezone),
+            world_region: c_string(zipcode.world_region.as_str()),
+            lat: coordinates.map_or(f64::NAN, |c| c.lat),
+            long: coordinates.map_or(f64::NAN, |c| c.long),
+            active: zipcode.active,
+            area_codes: zipcode.area_codes.iter().map(|s| c_string(s)).collect(),
+            acceptable_cities: zipcode.acceptable_cities.iter().map(|s| c_string(s)).collect(),
+            unacceptable_cities: zipcode.unacceptable_cities.iter().map(|s| c_string(s)).collect(),
+        }
+    }
+}
+
+/// Copy a string into C, dropping any interior NUL bytes, which the dataset never contains.
+fn c_string(s: &str) -> CString {
+    CString::new(s.replace('\0', "")).expect("NUL bytes were removed")
+}
+
+/// Run `f`, converting a null or non-UTF-8 `zipcode` into an error code and a panic into
+/// `ZipcodesError::Panic` instead of unwinding into C.
+unsafe fn with_zipcode<F>(zipcode: *const c_char, f: F) -> ZipcodesError
+where
+    F: FnOnce(&str) -> crate::Result<()> + UnwindSafe,
+{
+    if zipcode.is_null() {
+        return ZipcodesError::NullPointer;
+    }
+    let zipcode = match CStr::from_ptr(zipcode).to_str() {
+        Ok(zipcode) => zipcode,
+        Err(_) => return ZipcodesError::InvalidCharacters,
+    };
+    match catch_unwind(|| f(zipcode)) {
+        Ok(Ok(())) => ZipcodesError::Ok,
+        Ok(Err(e)) => ZipcodesError::from(&e),
+        Err(_) => ZipcodesError::Panic,
+    }
+}
+
+/// Load the embedded database ahead of the first query.
+#[no_mangle]
+pub extern "C" fn zipcodes_init() -> ZipcodesError {
+    match catch_unwind(crate::init) {
+        Ok(Ok(())) => ZipcodesError::Ok,
+        Ok(Err(e)) => ZipcodesError::from(&e),
+        Err(_) => ZipcodesError::Panic,
+    }
+}
+
+/// Write whether `zipcode` exists in the database to `out`.
+///
+/// # Safety
+///
+/// `zipcode` must be null or a valid NUL-terminated string, and `out` must be null or valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_is_real(zipcode: *const c_char, out: *mut bool) -> ZipcodesError {
+    if out.is_null() {
+        return ZipcodesError::NullPointer;
+    }
+    let out = ptr::NonNull::new_unchecked(out);
+    with_zipcode(zipcode, move |zipcode| {
+        *out.as_ptr() = crate::is_real(zipcode)?;
+        Ok(())
+    })
+}
+
+/// Write a new list of the records matching `zipcode` to `out`. The list may be empty, and must
+/// be released with `zipcodes_list_free`.
+///
+/// # Safety
+///
+/// `zipcode` must be null or a valid NUL-terminated string, and `out` must be null or valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_matching(zipcode: *const c_char, out: *mut *mut ZipcodesList) -> ZipcodesError {
+    if out.is_null() {
+        return ZipcodesError::NullPointer;
+    }
+    *out = ptr::null_mut();
+    let out = ptr::NonNull::new_unchecked(out);
+    with_zipcode(zipcode, move |zipcode| {
+        let zipcodes = crate::matching_ref(zipcode)?.iter().map(ZipcodesZipcode::new).collect();
+        *out.as_ptr() = Box::into_raw(Box::new(ZipcodesList { zipcodes }));
+        Ok(())
+    })
+}
+
+/// Write a new handle to the record for `zipcode` to `out`, or fail with
+/// `ZIPCODES_ERROR_UNKNOWN_ZIPCODE` if there is none. The handle must be released with
+/// `zipcodes_zipcode_free`.
+///
+/// # Safety
+///
+/// `zipcode` must be null or a valid NUL-terminated string, and `out` must be null or valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_lookup(zipcode: *const c_char, out: *mut *mut ZipcodesZipcode) -> ZipcodesError {
+    if out.is_null() {
+        return ZipcodesError::NullPointer;
+    }
+    *out = ptr::null_mut();
+    let out = ptr::NonNull::new_unchecked(out);
+    with_zipcode(zipcode, move |zipcode| {
+        let zip5 = crate::ToZip5::to_zip5(zipcode)?;
+        let found = crate::get(zip5)?.ok_or(Error::UnknownZipcode(zip5))?;
+        *out.as_ptr() = Box::into_raw(Box::new(ZipcodesZipcode::new(found)));
+        Ok(())
+    })
+}
+
+/// A static, human-readable description of an error code.
+#[no_mangle]
+pub extern "C" fn zipcodes_error_message(error: ZipcodesError) -> *const c_char {
+    let message: &'static [u8] = match error {
+        ZipcodesError::Ok => b"Success.\0",
+        ZipcodesError::NullPointer => b"A required pointer argument was null.\0",
+        ZipcodesError::InvalidFormat => b"Invalid format, zipcode must be of the format: \"#####\" or \"#####-####\"\0",
+        ZipcodesError::InvalidCharacters => b"Invalid characters, zipcode may only contain digits and \"-\".\0",
+        ZipcodesError::Empty => b"Invalid format, zipcode must not be empty.\0",
+        ZipcodesError::InvalidSeparator => b"Invalid separator, the ZIP+4 extension must be separated by \"-\" or \" \".\0",
+        ZipcodesError::MissingPlus4 => b"Missing ZIP+4 extension, zipcode must be of the format: \"#####-####\"\0",
+        ZipcodesError::UnknownZipcode => b"Unknown zipcode, it does not exist in the database.\0",
+        ZipcodesError::MissingCoordinates => b"Missing coordinates, zipcode has no usable latitude and longitude.\0",
+        ZipcodesError::InvalidRadius => b"Invalid radius, radius must be a finite, non-negative distance.\0",
+        ZipcodesError::InvalidCoordinates => b"Invalid coordinates, latitude must be within [-90, 90] and longitude within [-180, 180].\0",
+        ZipcodesError::UnknownVariant => b"Unknown value, it is not a value used by the zipcode database.\0",
+        ZipcodesError::InvalidPrefixLength => b"Invalid prefix length, zipcode prefix must contain between 1 and 5 digits.\0",
+        ZipcodesError::InvalidPrefixCharacters => b"Invalid prefix characters, zipcode prefix may only contain digits.\0",
+        ZipcodesError::Io => b"Failed to read zipcode dataset.\0",
+        ZipcodesError::Json => b"Failed to deserialize zipcode dataset.\0",
+        ZipcodesError::Decompress => b"Failed to decompress zipcode dataset.\0",
+        ZipcodesError::Utf8 => b"Invalid zipcode dataset, it is not valid UTF-8.\0",
+        ZipcodesError::Schema => b"Invalid zipcode record in dataset.\0",
+        ZipcodesError::Embedded => b"Failed to load the embedded zipcode database.\0",
+        ZipcodesError::Unsupported => b"Unsupported dataset format, the library was built without the feature it requires.\0",
+        ZipcodesError::InvalidRecord => b"Invalid source record in dataset.\0",
+        ZipcodesError::InvalidAreaCode => b"Invalid area code, area code must be three digits starting with 2 to 9.\0",
+        ZipcodesError::InvalidPhoneNumber => b"Invalid phone number, it must be a ten digit North American number.\0",
+        ZipcodesError::InvalidCompact => b"Invalid compact dataset.\0",
+        ZipcodesError::Panic => b"An unexpected internal error occurred.\0",
+    };
+    message.as_ptr() as *const c_char
+}
+
+/// The number of records in a list, or 0 for a null list.
+///
+/// # Safety
+///
+/// `list` must be null or a live handle returned by `zipcodes_matching`.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_list_len(list: *const ZipcodesList) -> usize {
+    list.as_ref().map_or(0, |l| l.zipcodes.len())
+}
+
+/// Borrow the record at `index` in a list, or null if it is out of bounds. The record lives as
+/// long as the list and must not be freed on its own.
+///
+/// # Safety
+///
+/// `list` must be null or a live handle returned by `zipcodes_matching`.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_list_get(list: *const ZipcodesList, index: usize) -> *const ZipcodesZipcode {
+    list.as_ref()
+        .and_then(|l| l.zipcodes.get(index))
+        .map_or(ptr::null(), |z| z as *const ZipcodesZipcode)
+}
+
+/// Release a list returned by `zipcodes_matching`. Null is ignored.
+///
+/// # Safety
+///
+/// `list` must be null or a live handle returned by `zipcodes_matching`, and must not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_list_free(list: *mut ZipcodesList) {
+    if !list.is_null() {
+        drop(Box::from_raw(list));
+    }
+}
+
+/// Release a record returned by `zipcodes_lookup`. Null is ignored.
+///
+/// # Safety
+///
+/// `zipcode` must be null or a live handle returned by `zipcodes_lookup`, and must not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_zipcode_free(zipcode: *mut ZipcodesZipcode) {
+    if !zipcode.is_null() {
+        drop(Box::from_raw(zipcode));
+    }
+}
+
+/// The 5-digit zipcode.
+///
+/// # Safety
+///
+/// `zipcode` must be null or a live record handle. The string lives as long as it.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_zipcode_zip_code(zipcode: *const ZipcodesZipcode) -> *const c_char {
+    zipcode.as_ref().map_or(ptr::null(), |z| z.zip_code.as_ptr())
+}
+
+/// The zipcode type: "STANDARD", "PO BOX", "UNIQUE" or "MILITARY".
+///
+/// # Safety
+///
+/// `zipcode` must be null or a live record handle. The string lives as long as it.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_zipcode_type(zipcode: *const ZipcodesZipcode) -> *const c_char {
+    zipcode.as_ref().map_or(ptr::null(), |z| z.zip_code_type.as_ptr())
+}
+
+/// The preferred city name.
+///
+/// # Safety
+///
+/// `zipcode` must be null or a live record handle. The string lives as long as it.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_zipcode_city(zipcode: *const ZipcodesZipcode) -> *const c_char {
+    zipcode.as_ref().map_or(ptr::null(), |z| z.city.as_ptr())
+}
+
+/// The two-letter state code.
+///
+/// # Safety
+///
+/// `zipcode` must be null or a live record handle. The string lives as long as it.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_zipcode_state(zipcode: *const ZipcodesZipcode) -> *const c_char {
+    zipcode.as_ref().map_or(ptr::null(), |z| z.state.as_ptr())
+}
+
+/// The county name, which may be empty.
+///
+/// # Safety
+///
+/// `zipcode` must be null or a live record handle. The string lives as long as it.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_zipcode_county(zipcode: *const ZipcodesZipcode) -> *const c_char {
+    zipcode.as_ref().map_or(ptr::null(), |z| z.county.as_ptr())
+}
+
+/// The two-letter country code, which may be empty.
+///
+/// # Safety
+///
+/// `zipcode` must be null or a live record handle. The string lives as long as it.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_zipcode_country(zipcode: *const ZipcodesZipcode) -> *const c_char {
+    zipcode.as_ref().map_or(ptr::null(), |z| z.country.as_ptr())
+}
+
+/// The IANA timezone name, which may be empty.
+///
+/// # Safety
+///
+/// `zipcode` must be null or a live record handle. The string lives as long as it.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_zipcode_timezone(zipcode: *const ZipcodesZipcode) -> *const c_char {
+    zipcode.as_ref().map_or(ptr::null(), |z| z.timezone.as_ptr())
+}
+
+/// The world region code, which may be empty.
+///
+/// # Safety
+///
+/// `zipcode` must be null or a live record handle. The string lives as long as it.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_zipcode_world_region(zipcode: *const ZipcodesZipcode) -> *const c_char {
+    zipcode.as_ref().map_or(ptr::null(), |z| z.world_region.as_ptr())
+}
+
+/// The number of telephone area codes serving the zipcode.
+///
+/// # Safety
+///
+/// `zipcode` must be null or a live record handle.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_zipcode_area_codes_len(zipcode: *const ZipcodesZipcode) -> usize {
+    zipcode.as_ref().map_or(0, |z| z.area_codes.len())
+}
+
+/// One of the telephone area codes serving the zipcode, or null if `index` is out of bounds.
+///
+/// # Safety
+///
+/// `zipcode` must be null or a live record handle. The string lives as long as it.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_zipcode_area_code(zipcode: *const ZipcodesZipcode, index: usize) -> *const c_char {
+    zipcode.as_ref()
+        .and_then(|z| z.area_codes.get(index))
+        .map_or(ptr::null(), |s| s.as_ptr())
+}
+
+/// The number of alternative city names USPS accepts for the zipcode.
+///
+/// # Safety
+///
+/// `zipcode` must be null or a live record handle.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_zipcode_acceptable_cities_len(zipcode: *const ZipcodesZipcode) -> usize {
+    zipcode.as_ref().map_or(0, |z| z.acceptable_cities.len())
+}
+
+/// One of the alternative city names USPS accepts for the zipcode, or null if `index` is out of bounds.
+///
+/// # Safety
+///
+/// `zipcode` must be null or a live record handle. The string lives as long as it.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_zipcode_acceptable_city(zipcode: *const ZipcodesZipcode, index: usize) -> *const c_char {
+    zipcode.as_ref()
+        .and_then(|z| z.acceptable_cities.get(index))
+        .map_or(ptr::null(), |s| s.as_ptr())
+}
+
+/// The number of city names USPS does not accept for the zipcode.
+///
+/// # Safety
+///
+/// `zipcode` must be null or a live record handle.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_zipcode_unacceptable_cities_len(zipcode: *const ZipcodesZipcode) -> usize {
+    zipcode.as_ref().map_or(0, |z| z.unacceptable_cities.len())
+}
+
+/// One of the city names USPS does not accept for the zipcode, or null if `index` is out of bounds.
+///
+/// # Safety
+///
+/// `zipcode` must be null or a live record handle. The string lives as long as it.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_zipcode_unacceptable_city(zipcode: *const ZipcodesZipcode, index: usize) -> *const c_char {
+    zipcode.as_ref()
+        .and_then(|z| z.unacceptable_cities.get(index))
+        .map_or(ptr::null(), |s| s.as_ptr())
+}
+
+/// The latitude of the zipcode's centroid, or NaN if it has no usable coordinates.
+///
+/// # Safety
+///
+/// `zipcode` must be null or a live record handle.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_zipcode_lat(zipcode: *const ZipcodesZipcode) -> f64 {
+    zipcode.as_ref().map_or(f64::NAN, |z| z.lat)
+}
+
+/// The longitude of the zipcode's centroid, or NaN if it has no usable coordinates.
+///
+/// # Safety
+///
+/// `zipcode` must be null or a live record handle.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_zipcode_long(zipcode: *const ZipcodesZipcode) -> f64 {
+    zipcode.as_ref().map_or(f64::NAN, |z| z.long)
+}
+
+/// Whether the zipcode is still in use.
+///
+/// # Safety
+///
+/// `zipcode` must be null or a live record handle.
+#[no_mangle]
+pub unsafe extern "C" fn zipcodes_zipcode_active(zipcode: *const ZipcodesZipcode) -> bool {
+    zipcode.as_ref().is_some_and(|z| z.active)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(any(feature = "bzip2", feature = "static-data"))]
+    unsafe fn string(s: *const c_char) -> &'static str {
+        CStr::from_ptr(s).to_str().unwrap()
+    }
+
+    #[cfg(any(feature = "bzip2", feature = "static-data"))]
+    #[test]
+    fn should_round_trip_through_the_c_api() {
+        unsafe {
+            let mut real = false;
+            assert_eq!(zipcodes_is_real(b"77429-1145\0".as_ptr() as *const c_char, &mut real), ZipcodesError::Ok);
+            assert!(real);
+            assert_eq!(zipcodes_is_real(b"0646a\0".as_ptr() as *const c_char, &mut real), ZipcodesError::InvalidCharacters);
+            assert_eq!(zipcodes_is_real(ptr::null(), &mut real), ZipcodesError::NullPointer);
+
+            let mut zipcode = ptr::null_mut();
+            assert_eq!(zipcodes_lookup(b"06475\0".as_ptr() as *const c_char, &mut zipcode), ZipcodesError::Ok);
+            assert_eq!(string(zipcodes_zipcode_city(zipcode)), "Old Saybrook");
+            assert_eq!(string(zipcodes_zipcode_state(zipcode)), "CT");
+            assert_eq!(string(zipcodes_zipcode_type(zipcode)), "STANDARD");
+            assert_eq!(zipcodes_zipcode_unacceptable_cities_len(zipcode), 1);
+            assert_eq!(string(zipcodes_zipcode_unacceptable_city(zipcode, 0)), "Fenwick");
+            assert!(zipcodes_zipcode_unacceptable_city(zipcode, 1).is_null());
+            assert!((zipcodes_zipcode_lat(zipcode) - 41.3015).abs() < 1e-9);
+            assert!(zipcodes_zipcode_active(zipcode));
+            zipcodes_zipcode_free(zipcode);
+
+            assert_eq!(zipcodes_lookup(b"00000\0".as_ptr() as *const c_char, &mut zipcode), ZipcodesError::UnknownZipcode);
+            assert!(zipcode.is_null());
+
+            let mut list = ptr::null_mut();
+            assert_eq!(zipcodes_matching(b"77429\0".as_ptr() as *const c_char, &mut list), ZipcodesError::Ok);
+            assert_eq!(zipcodes_list_len(list), 1);
+            assert_eq!(string(zipcodes_zipcode_zip_code(zipcodes_list_get(list, 0))), "77429");
+            assert!(zipcodes_list_get(list, 1).is_null());
+            zipcodes_list_free(list);
+
+            assert!(string(zipcodes_error_message(ZipcodesError::InvalidFormat)).starts_with("Invalid format"));
+        }
+    }
+
+    #[test]
+    fn should_match_the_header() {
+        use ZipcodesError::*;
+        let codes = [
+            Ok, NullPointer, InvalidFormat, InvalidCharacters, Empty, InvalidSeparator, MissingPlus4, UnknownZipcode,
+            MissingCoordinates, InvalidRadius, InvalidCoordinates, UnknownVariant, InvalidPrefixLength,
+            InvalidPrefixCharacters, Io, Json, Decompress, Utf8, Schema, Embedded, Unsupported, InvalidRecord,
+            InvalidAreaCode, InvalidPhoneNumber, InvalidCompact, Panic,
+        ];
+        let expected = codes.iter()
+            .map(|code| {
+                let name = format!("{:?}", code).chars().enumerate().fold(String::new(), |mut name, (i, c)| {
+                    if i > 0 && c.is_ascii_uppercase() {
+                        name.push('_');
+                    }
+                    name.push(c.to_ascii_uppercase());
+                    name
+                });
+                format!("ZIPCODES_ERROR_{} = {},", name, *code as i32)
+            })
+            .collect::<Vec<_>>();
+        let header = include_str!("../include/zipcodes.h");
+        let start = header.find("typedef enum ZipcodesError {").unwrap();
+        let end = start + header[start..].find("} ZipcodesError;").unwrap();
+        assert_eq!(header[start..end].lines().skip(1).map(str::trim).collect::<Vec<_>>(), expected);
+        assert_eq!(Panic as i32, -1);
+    }
+}
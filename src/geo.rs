@@ -0,0 +1,419 @@
+use serde::{Deserialize, Serialize};
+
+use crate::spatial::{chord_length, to_point};
+use crate::{try_load, Error, Result, ToZip5, Zip5, ZipPlus4, Zipcode, ZipcodeDb, ZipcodeType};
+
+const EARTH_RADIUS_MILES: f64 = 3958.7613;
+const EARTH_RADIUS_KILOMETERS: f64 = 6371.0088;
+
+// WGS-84 ellipsoid, in meters.
+const WGS84_A: f64 = 6_378_137.0;
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+const WGS84_B: f64 = WGS84_A * (1.0 - WGS84_F);
+const METERS_PER_MILE: f64 = 1609.344;
+
+/// The unit that a distance is expressed in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Unit {
+    Miles,
+    Kilometers,
+}
+
+impl Unit {
+    fn earth_radius(self) -> f64 {
+        match self {
+            Unit::Miles => EARTH_RADIUS_MILES,
+            Unit::Kilometers => EARTH_RADIUS_KILOMETERS,
+        }
+    }
+
+    fn convert_meters(self, meters: f64) -> f64 {
+        match self {
+            Unit::Miles => meters / METERS_PER_MILE,
+            Unit::Kilometers => meters / 1000.0,
+        }
+    }
+}
+
+/// A latitude and longitude in decimal degrees.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Coordinates {
+    pub lat: f64,
+    pub long: f64,
+}
+
+impl Coordinates {
+    /// Create coordinates from a latitude and longitude in decimal degrees, returning `None` when
+    /// either is out of range.
+    pub fn new(lat: f64, long: f64) -> Option<Self> {
+        if (-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&long) {
+            Some(Coordinates { lat, long })
+        } else {
+            None
+        }
+    }
+
+    /// The great-circle distance to `other` on a spherical earth, using the haversine formula.
+    pub fn haversine(&self, other: &Coordinates, unit: Unit) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let dlat = lat2 - lat1;
+        let dlong = (other.long - self.long).to_radians();
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlong / 2.0).sin().powi(2);
+        2.0 * unit.earth_radius() * a.sqrt().asin()
+    }
+
+    /// The geodesic distance to `other` on the WGS-84 ellipsoid, using Vincenty's inverse formula.
+    ///
+    /// Returns `None` if the iteration fails to converge, which only happens for nearly antipodal
+    /// points.
+    pub fn vincenty(&self, other: &Coordinates, unit: Unit) -> Option<f64> {
+        let l = (other.long - self.long).to_radians();
+        let u1 = ((1.0 - WGS84_F) * self.lat.to_radians().tan()).atan();
+        let u2 = ((1.0 - WGS84_F) * other.lat.to_radians().tan()).atan();
+        let (sin_u1, cos_u1) = u1.sin_cos();
+        let (sin_u2, cos_u2) = u2.sin_cos();
+
+        let mut lambda = l;
+        for _ in 0..200 {
+            let (sin_lambda, cos_lambda) = lambda.sin_cos();
+            let sin_sigma = ((cos_u2 * sin_lambda).powi(2)
+                + (cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda).powi(2))
+            .sqrt();
+            if sin_sigma == 0.0 {
+                return Some(0.0);
+            }
+            let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+            let sigma = sin_sigma.atan2(cos_sigma);
+            let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+            let cos_sq_alpha = 1.0 - sin_alpha * sin_alpha;
+            let cos_2sigma_m = if cos_sq_alpha == 0.0 { 0.0 } else { cos_sigma - 2.0 * sin_u1 * sin_u2 / cos_sq_alpha };
+            let c = WGS84_F / 16.0 * cos_sq_alpha * (4.0 + WGS84_F * (4.0 - 3.0 * cos_sq_alpha));
+            let previous = lambda;
+            lambda = l + (1.0 - c) * WGS84_F * sin_alpha
+                * (sigma + c * sin_sigma * (cos_2sigma_m + c * cos_sigma * (-1.0 + 2.0 * cos_2sigma_m * cos_2sigma_m)));
+            if (lambda - previous).abs() < 1e-12 {
+                let u_sq = cos_sq_alpha * (WGS84_A * WGS84_A - WGS84_B * WGS84_B) / (WGS84_B * WGS84_B);
+                let a = 1.0 + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+                let b = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+                let delta_sigma = b * sin_sigma
+                    * (cos_2sigma_m + b / 4.0
+                        * (cos_sigma * (-1.0 + 2.0 * cos_2sigma_m * cos_2sigma_m)
+                            - b / 6.0 * cos_2sigma_m * (-3.0 + 4.0 * sin_sigma * sin_sigma)
+                                * (-3.0 + 4.0 * cos_2sigma_m * cos_2sigma_m)));
+                return Some(unit.convert_meters(WGS84_B * a * (sigma - delta_sigma)));
+            }
+        }
+        None
+    }
+}
+
+impl Zipcode {
+    /// The parsed latitude and longitude of the zipcode's centroid.
+    ///
+    /// Fails with `Error::MissingCoordinates` when the record has no usable coordinates, such as
+    /// the `0, 0` placeholders used by most military zipcodes.
+    pub fn coordinates(&self) -> Result<Coordinates> {
+        let missing = || Error::MissingCoordinates(self.zip_code.clone());
+        let lat = self.lat.trim().parse::<f64>().map_err(|_| missing())?;
+        let long = self.long.trim().parse::<f64>().map_err(|_| missing())?;
+        if lat == 0.0 && long == 0.0 {
+            return Err(missing());
+        }
+        Coordinates::new(lat, long).ok_or_else(missing)
+    }
+}
+
+/// Anything that can be resolved to a point on the map.
+///
+/// Implemented for zipcode records and raw coordinates, and for strings and typed zipcodes,
+/// which are looked up in a database.
+pub trait Locate {
+    /// Resolve the value to coordinates, looking zipcodes up in the supplied database.
+    fn locate(&self, db: &ZipcodeDb) -> Result<Coordinates>;
+
+    /// Resolve the value to coordinates without a database, as for records and raw coordinates,
+    /// or return `None` if it has to be looked up.
+    fn locate_standalone(&self) -> Option<Result<Coordinates>> {
+        None
+    }
+}
+
+impl Locate for Coordinates {
+    fn locate(&self, _: &ZipcodeDb) -> Result<Coordinates> {
+        Ok(*self)
+    }
+
+    fn locate_standalone(&self) -> Option<Result<Coordinates>> {
+        Some(Ok(*self))
+    }
+}
+
+impl Locate for Zipcode {
+    fn locate(&self, _: &ZipcodeDb) -> Result<Coordinates> {
+        self.coordinates()
+    }
+
+    fn locate_standalone(&self) -> Option<Result<Coordinates>> {
+        Some(self.coordinates())
+    }
+}
+
+impl Locate for str {
+    fn locate(&self, db: &ZipcodeDb) -> Result<Coordinates> {
+        locate_zipcode(self, db)
+    }
+}
+
+impl Locate for String {
+    fn locate(&self, db: &ZipcodeDb) -> Result<Coordinates> {
+        locate_zipcode(self, db)
+    }
+}
+
+impl Locate for Zip5 {
+    fn locate(&self, db: &ZipcodeDb) -> Result<Coordinates> {
+        locate_zipcode(self, db)
+    }
+}
+
+impl Locate for ZipPlus4 {
+    fn locate(&self, db: &ZipcodeDb) -> Result<Coordinates> {
+        locate_zipcode(self, db)
+    }
+}
+
+impl<T: Locate + ?Sized> Locate for &T {
+    fn locate(&self, db: &ZipcodeDb) -> Result<Coordinates> {
+        (**self).locate(db)
+    }
+
+    fn locate_standalone(&self) -> Option<Result<Coordinates>> {
+        (**self).locate_standalone()
+    }
+}
+
+fn locate_zipcode<Z: ToZip5>(zipcode: Z, db: &ZipcodeDb) -> Result<Coordinates> {
+    let zipcode = zipcode.to_zip5()?;
+    db.get(zipcode)?.ok_or(Error::UnknownZipcode(zipcode))?.coordinates()
+}
+
+/// Options restricting which zipcodes `nearest` may return.
+#[derive(Clone, Debug, Default)]
+pub struct NearestOptions {
+    /// Skip zipcodes that are no longer active.
+    pub active_only: bool,
+    /// Only return zipcodes of these types. Empty allows every type.
+    pub zip_code_types: Vec<ZipcodeType>,
+    /// Never return zipcodes of these types, e.g. `ZipcodeType::PoBox` or `ZipcodeType::Unique`.
+    pub exclude_zip_code_types: Vec<ZipcodeType>,
+}
+
+impl NearestOptions {
+    fn accepts(&self, zipcode: &Zipcode) -> bool {
+        (!self.active_only || zipcode.active)
+            && (self.zip_code_types.is_empty() || self.zip_code_types.contains(&zipcode.zip_code_type))
+            && !self.exclude_zip_code_types.contains(&zipcode.zip_code_type)
+    }
+}
+
+impl ZipcodeDb {
+    /// The great-circle distance between two zipcodes, as in `crate::distance`.
+    pub fn distance<A: Locate, B: Locate>(&self, a: A, b: B, unit: Unit) -> Result<f64> {
+        Ok(a.locate(self)?.haversine(&b.locate(self)?, unit))
+    }
+
+    /// Borrow every zipcode within `radius` of `center`, closest first, along with its distance,
+    /// as in `crate::within_radius_ref`.
+    pub fn within_radius<C: Locate>(&self, center: C, radius: f64, unit: Unit) -> Result<Vec<(&Zipcode, f64)>> {
+        let center = center.locate(self)?;
+        check_radius(radius)?;
+        // Widen the search slightly so that rounding never drops a zipcode right on the boundary;
+        // the exact haversine distance decides membership below.
+        let chord = chord_length(radius / unit.earth_radius()) + 1e-9;
+        let mut found = self.spatial().within(&to_point(&center), chord).into_iter()
+            .filter_map(|i| {
+                let z = &self.zipcodes()[*i];
+                let distance = center.haversine(&z.coordinates().ok()?, unit);
+                (distance <= radius).then_some((z, distance))
+            })
+            .collect::<Vec<_>>();
+        found.sort_by(|a, b| a.1.total_cmp(&b.1));
+        Ok(found)
+    }
+
+    /// Borrow the `k` zipcodes closest to the supplied latitude and longitude, as in
+    /// `crate::nearest_ref`.
+    pub fn nearest(&self, lat: f64, long: f64, k: usize, options: &NearestOptions) -> Result<Vec<&Zipcode>> {
+        let center = Coordinates::new(lat, long).ok_or(Error::InvalidCoordinates(lat, long))?;
+        let zipcodes = self.zipcodes();
+        Ok(self.spatial().nearest(&to_point(&center), k, |i| options.accepts(&zipcodes[*i]))
+            .into_iter()
+            .map(|(_, i)| &zipcodes[*i])
+            .collect())
+    }
+}
+
+/// The great-circle distance between two zipcodes, using the haversine formula.
+///
+/// Either side may be a zipcode string, a `Zip5` or `ZipPlus4`, a `Zipcode` record or a set of
+/// `Coordinates`.
+pub fn distance<A: Locate, B: Locate>(a: A, b: B, unit: Unit) -> Result<f64> {
+    try_load()?.distance(a, b, unit)
+}
+
+/// Return every zipcode within `radius` of `center`, closest first, along with its distance from
+/// `center` in the supplied unit. Zipcodes without usable coordinates are never included.
+///
+/// `center` may be a zipcode string, a `Zip5` or `ZipPlus4`, a `Zipcode` record or a set of
+/// `Coordinates`. By default, the supplied list of zipcodes is everything stored in the
+/// database. However, an optional list of override zipcodes can be supplied.
+pub fn within_radius<C: Locate>(center: C, radius: f64, unit: Unit, zipcodes: Option<Vec<Zipcode>>) -> Result<Vec<(Zipcode, f64)>> {
+    match zipcodes {
+        Some(zipcodes) => {
+            // Only a zipcode center needs the embedded database; records and coordinates do not.
+            let center = match center.locate_standalone() {
+                Some(center) => center?,
+                None => center.locate(try_load()?)?,
+            };
+            check_radius(radius)?;
+            let mut found = zipcodes.into_iter()
+                .filter_map(|z| {
+                    let distance = center.haversine(&z.coordinates().ok()?, unit);
+                    (distance <= radius).then_some((z, distance))
+                })
+                .collect::<Vec<_>>();
+            found.sort_by(|a, b| a.1.total_cmp(&b.1));
+            Ok(found)
+        }
+        None => Ok(within_radius_ref(center, radius, unit)?.into_iter().map(|(z, d)| (z.clone(), d)).collect()),
+    }
+}
+
+/// Borrow every zipcode in the database within `radius` of `center`, closest first, along with
+/// its distance from `center` in the supplied unit.
+///
+/// This is the borrowing counterpart of `within_radius` when no override list is needed.
+pub fn within_radius_ref<C: Locate>(center: C, radius: f64, unit: Unit) -> Result<Vec<(&'static Zipcode, f64)>> {
+    try_load()?.within_radius(center, radius, unit)
+}
+
+/// Return the `k` zipcodes whose centroids are closest to the supplied latitude and longitude,
+/// closest first, skipping any rejected by `options`.
+pub fn nearest(lat: f64, long: f64, k: usize, options: &NearestOptions) -> Result<Vec<Zipcode>> {
+    Ok(nearest_ref(lat, long, k, options)?.into_iter().cloned().collect())
+}
+
+/// Borrow the `k` zipcodes in the database whose centroids are closest to the supplied latitude
+/// and longitude, closest first, skipping any rejected by `options`.
+///
+/// This is the borrowing counterpart of `nearest`.
+pub fn nearest_ref(lat: f64, long: f64, k: usize, options: &NearestOptions) -> Result<Vec<&'static Zipcode>> {
+    try_load()?.nearest(lat, long, k, options)
+}
+
+fn check_radius(radius: f64) -> Result<()> {
+    if radius.is_finite() && radius >= 0.0 {
+        Ok(())
+    } else {
+        Err(Error::InvalidRadius(radius))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(any(feature = "bzip2", feature = "static-data"))]
+    use crate::{get, list_all};
+
+    #[cfg(any(feature = "bzip2", feature = "static-data"))]
+    #[test]
+    fn should_measure_distance_between_zipcodes() {
+        // Cypress, TX to Houston, TX.
+        let miles = distance("77429", "77002", Unit::Miles).unwrap();
+        assert!((20.0..30.0).contains(&miles), "{}", miles);
+        let kilometers = distance("77429", "77002", Unit::Kilometers).unwrap();
+        assert!((kilometers / miles - 1.609).abs() < 0.01);
+        assert_eq!(distance("77429", "77429", Unit::Miles).unwrap(), 0.0);
+    }
+
+    #[cfg(any(feature = "bzip2", feature = "static-data"))]
+    #[test]
+    fn should_agree_with_vincenty() {
+        let a = get("77429").unwrap().unwrap().coordinates().unwrap();
+        let b = get("06903").unwrap().unwrap().coordinates().unwrap();
+        let haversine = a.haversine(&b, Unit::Kilometers);
+        let vincenty = a.vincenty(&b, Unit::Kilometers).unwrap();
+        assert!((haversine - vincenty).abs() / vincenty < 0.005);
+    }
+
+    #[cfg(any(feature = "bzip2", feature = "static-data"))]
+    #[test]
+    fn should_find_zipcodes_within_radius() {
+        let found = within_radius_ref("77429", 10.0, Unit::Miles).unwrap();
+        assert_eq!(found[0].0.zip_code, "77429");
+        assert_eq!(found[0].1, 0.0);
+        assert!(found.windows(2).all(|w| w[0].1 <= w[1].1));
+        assert!(found.iter().all(|(_, d)| *d <= 10.0));
+
+        let center = get("77429").unwrap().unwrap().coordinates().unwrap();
+        let expected = try_load().unwrap().zipcodes().iter()
+            .filter(|z| z.coordinates().is_ok_and(|c| center.haversine(&c, Unit::Miles) <= 10.0))
+            .count();
+        assert_eq!(found.len(), expected);
+
+        let overrides = within_radius("77429", 10.0, Unit::Miles, Some(list_all().unwrap())).unwrap();
+        assert_eq!(overrides.len(), expected);
+        assert!(matches!(within_radius_ref("77429", -1.0, Unit::Miles), Err(Error::InvalidRadius(_))));
+    }
+
+    #[test]
+    fn should_search_override_lists_without_the_dataset() {
+        let zipcode = |zip_code: &str, lat: &str, long: &str| -> Zipcode {
+            serde_json::from_value(serde_json::json!({
+                "acceptable_cities": [], "active": true, "area_codes": [], "city": "", "country": "US",
+                "county": "", "lat": lat, "long": long, "state": "TX", "timezone": "",
+                "unacceptable_cities": [], "world_region": "NA", "zip_code": zip_code, "zip_code_type": "STANDARD",
+            }))
+            .unwrap()
+        };
+        let overrides = vec![zipcode("77429", "29.9857", "-95.6548"), zipcode("06475", "41.3015", "-72.3879")];
+        let center = Coordinates::new(29.9857, -95.6548).unwrap();
+        let found = within_radius(center, 10.0, Unit::Miles, Some(overrides.clone())).unwrap();
+        assert_eq!(found.iter().map(|(z, _)| z.zip_code.as_str()).collect::<Vec<_>>(), ["77429"]);
+        assert_eq!(within_radius(&overrides[1], 1.0, Unit::Miles, Some(overrides.clone())).unwrap().len(), 1);
+    }
+
+    #[cfg(any(feature = "bzip2", feature = "static-data"))]
+    #[test]
+    fn should_find_nearest_zipcodes() {
+        let cypress = get("77429").unwrap().unwrap();
+        let found = nearest_ref(29.9857, -95.6548, 5, &NearestOptions::default()).unwrap();
+        assert_eq!(found.len(), 5);
+        assert_eq!(found[0].zip_code, "77429");
+        let distances = found.iter().map(|z| distance(cypress, *z, Unit::Miles).unwrap()).collect::<Vec<_>>();
+        assert!(distances.windows(2).all(|w| w[0] <= w[1]));
+        let farthest = distances[4];
+        let closer = try_load().unwrap().zipcodes().iter()
+            .filter(|z| z.coordinates().is_ok_and(|c| cypress.coordinates().unwrap().haversine(&c, Unit::Miles) < farthest))
+            .count();
+        assert!(closer <= 5);
+
+        let options = NearestOptions {
+            active_only: true,
+            exclude_zip_code_types: vec![ZipcodeType::PoBox, ZipcodeType::Unique],
+            ..NearestOptions::default()
+        };
+        let found = nearest(29.9857, -95.6548, 20, &options).unwrap();
+        assert_eq!(found.len(), 20);
+        assert!(found.iter().all(|z| z.active && matches!(z.zip_code_type, ZipcodeType::Standard | ZipcodeType::Military)));
+        assert!(nearest(29.9857, -95.6548, 0, &options).unwrap().is_empty());
+        assert!(matches!(nearest(91.0, 0.0, 1, &options), Err(Error::InvalidCoordinates(..))));
+    }
+
+    #[cfg(any(feature = "bzip2", feature = "static-data"))]
+    #[test]
+    fn should_fail_without_usable_coordinates() {
+        assert!(matches!(distance("09001", "77429", Unit::Miles), Err(Error::MissingCoordinates(z)) if z == "09001"));
+        assert!(matches!(distance("00000", "77429", Unit::Miles), Err(Error::UnknownZipcode(_))));
+    }
+}
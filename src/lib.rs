@@ -1,22 +1,41 @@
-use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use std::io::prelude::*;
-use bzip2::read::{BzDecoder};
 use debug_print::debug_println;
 
-const ZIPCODE_LENGTH: usize = 5;
+mod address;
+mod autocomplete;
+mod compact;
+mod dataset;
+mod db;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod geo;
+mod military;
+mod phone;
+mod query;
+mod search;
+mod spatial;
+#[cfg(feature = "static-data")]
+pub mod static_data;
+#[cfg(feature = "chrono")]
+mod timezone;
+mod types;
+mod zip;
 
-static ZIPCODE_BYTES_BZIP: &[u8] = include_bytes!("zips.json.bz2");
+pub use address::{city_state, validate_address_parts, AddressMatch, CityState, Suggestion, Validation};
+pub use autocomplete::{autocomplete, Completion};
+pub use dataset::{DatasetBuilder, DatasetStats};
+pub use db::{init, try_load, Format, ZipcodeDb};
+pub use geo::{distance, nearest, nearest_ref, within_radius, within_radius_ref, Coordinates, Locate, NearestOptions, Unit};
+pub use military::{military_info, validate_military_address, MilitaryInfo, MilitaryIssue, MilitaryPostOffice, MilitaryValidation};
+pub use phone::{by_area_code, phone_area_code, phone_matches_zip, ServiceArea};
+pub use query::Query;
+pub use search::{search_city, CityMatch};
+#[cfg(feature = "chrono")]
+pub use timezone::{local_time, timezone, utc_offset};
+pub use types::{State, WorldRegion, ZipcodeType};
+pub use zip::{ToZip5, Zip5, ZipPlus4};
 
-static ZIPCODES: Lazy<Vec<Zipcode>> = Lazy::new(|| {
-    let mut decompressor = BzDecoder::new(ZIPCODE_BYTES_BZIP);
-    let mut zipcode_json_bytes = String::new();
-    decompressor.read_to_string(&mut zipcode_json_bytes).unwrap();
-    match serde_json::from_str::<Vec<Zipcode>>(zipcode_json_bytes.as_str()) {
-        Ok(o) => o,
-        Err(e) => { panic!("failed to deserialize zipcode database: {}", e); }
-    }
-});
+const ZIPCODE_LENGTH: usize = 5;
 
 /// Describes different types of errors with supplied zipcodes during parsing.
 #[derive(thiserror::Error, Debug)]
@@ -25,61 +44,193 @@ pub enum Error {
     InvalidFormat,
     #[error("Invalid characters, zipcode may only contain digits and \"-\".")]
     InvalidCharacters,
+    #[error("Invalid format, zipcode must not be empty.")]
+    Empty,
+    #[error("Invalid separator {0:?}, the ZIP+4 extension must be separated by \"-\" or \" \".")]
+    InvalidSeparator(char),
+    #[error("Missing ZIP+4 extension, zipcode must be of the format: \"#####-####\"")]
+    MissingPlus4,
+    #[error("Unknown zipcode {0}, it does not exist in the database.")]
+    UnknownZipcode(Zip5),
+    #[error("Missing coordinates, zipcode {0} has no usable latitude and longitude.")]
+    MissingCoordinates(String),
+    #[error("Invalid radius {0}, radius must be a finite, non-negative distance.")]
+    InvalidRadius(f64),
+    #[error("Invalid coordinates ({0}, {1}), latitude must be within [-90, 90] and longitude within [-180, 180].")]
+    InvalidCoordinates(f64, f64),
+    #[error("Unknown {kind} {value:?}, it is not a value used by the zipcode database.")]
+    UnknownVariant { kind: &'static str, value: String },
+    #[error("Invalid prefix length, zipcode prefix must contain between 1 and 5 digits.")]
+    InvalidPrefixLength,
+    #[error("Invalid prefix characters, zipcode prefix may only contain digits.")]
+    InvalidPrefixCharacters,
+    #[error("Failed to read zipcode dataset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to deserialize zipcode dataset: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Failed to decompress zipcode dataset: {0}")]
+    Decompress(#[source] std::io::Error),
+    #[error("Invalid zipcode dataset, it is not valid UTF-8: {0}")]
+    Utf8(#[from] std::str::Utf8Error),
+    #[error("Invalid zipcode record at index {index}: {source}")]
+    Schema { index: usize, source: serde_json::Error },
+    #[error("Failed to load the embedded zipcode database: {0}")]
+    Embedded(std::sync::Arc<Error>),
+    #[error("Unsupported dataset format {0:?}, the crate was built without the feature it requires.")]
+    Unsupported(Format),
+    #[error("Invalid source record on line {line}: {reason}")]
+    InvalidRecord { line: usize, reason: String },
+    #[error("Invalid area code {0:?}, area code must be three digits starting with 2 to 9.")]
+    InvalidAreaCode(String),
+    #[error("Invalid phone number {0:?}, it must be a ten digit North American number, optionally preceded by \"+1\" or \"1\".")]
+    InvalidPhoneNumber(String),
+    #[error("Invalid compact dataset: {0}")]
+    InvalidCompact(String),
 }
 
 /// A result type where the error is an `Error`.
 pub type Result<T> = std::result::Result<T, Error>;
 
-/// Determine whether a supplied zipcode matches any existing zipcode. The supplied zipcode must be of the format: "#####", "#####-####", or "##### ####".
-pub fn matching(zipcode: &str, zipcodes: Option<Vec<Zipcode>>) -> Result<Vec<Zipcode>> {
-    let zipcode = clean_zipcode(zipcode)?;
-    let zipcodes = zipcodes.as_ref().unwrap_or(&ZIPCODES);
-    let matching_zipcodes = zipcodes.iter().filter(|z| z.zip_code == zipcode).cloned().collect::<Vec<_>>();
+/// Determine whether a supplied zipcode matches any existing zipcode. The supplied zipcode must be of the format: "#####", "#####-####", "##### ####" or "#########", or
+/// an already parsed `Zip5` or `ZipPlus4`.
+///
+/// Without an override list, the lookup goes through the zipcode index instead of scanning the
+/// whole database. With the `static-data` feature, it reads the static tables instead, copying
+/// only the matching records, so the database is never loaded.
+pub fn matching<Z: ToZip5>(zipcode: Z, zipcodes: Option<Vec<Zipcode>>) -> Result<Vec<Zipcode>> {
+    let zipcode = zipcode.to_zip5()?;
+    let matching_zipcodes = match zipcodes {
+        Some(zipcodes) => zipcodes.into_iter().filter(|z| z.zip_code == zipcode.as_str()).collect::<Vec<_>>(),
+        #[cfg(feature = "static-data")]
+        None => static_data::matching(zipcode)?.map(Zipcode::from).collect(),
+        #[cfg(not(feature = "static-data"))]
+        None => try_load()?.matching(zipcode)?,
+    };
     debug_println!("is_real matched {:?} zipcodes for {}", matching_zipcodes.len(), zipcode);
     Ok(matching_zipcodes)
 }
 
 /// Returns true if the supplied zipcode is a valid zipcode.
 ///
-/// This is mainly a wrapper around `is_real` that returns a `Result` instead of a `bool`.
-pub fn is_real(zipcode: &str) -> Result<bool> {
-    let zipcode = clean_zipcode(zipcode)?;
-    let matching_zipcodes = matching(zipcode, None)?;
-    Ok(!matching_zipcodes.is_empty())
+/// This is mainly a wrapper around `is_real` that returns a `Result` instead of a `bool`. With the
+/// `static-data` feature, it reads the static tables without loading the database or allocating.
+pub fn is_real<Z: ToZip5>(zipcode: Z) -> Result<bool> {
+    #[cfg(feature = "static-data")]
+    {
+        static_data::is_real(zipcode)
+    }
+    #[cfg(not(feature = "static-data"))]
+    {
+        try_load()?.is_real(zipcode)
+    }
+}
+
+/// Borrow the first zipcode in the database matching the supplied zipcode, without cloning it.
+///
+/// The supplied zipcode must be of the same format accepted by `matching`.
+pub fn get<Z: ToZip5>(zipcode: Z) -> Result<Option<&'static Zipcode>> {
+    try_load()?.get(zipcode)
+}
+
+/// Borrow every zipcode in the database matching the supplied zipcode, without cloning them.
+///
+/// This is the borrowing counterpart of `matching` when no override list is needed.
+pub fn matching_ref<Z: ToZip5>(zipcode: Z) -> Result<&'static [Zipcode]> {
+    try_load()?.matching_ref(zipcode)
+}
+
+/// Return the zipcodes whose `zip_code` begins with the supplied prefix of 1 to 5 digits.
+///
+/// By default, the supplied list of zipcodes is everything stored in the
+/// database. However, an optional list of override zipcodes can be supplied.
+pub fn similar_to(prefix: &str, zipcodes: Option<Vec<Zipcode>>) -> Result<Vec<Zipcode>> {
+    let prefix = clean_prefix(prefix)?;
+    let similar_zipcodes = match zipcodes {
+        Some(zipcodes) => zipcodes.into_iter().filter(|z| z.zip_code.starts_with(prefix)).collect::<Vec<_>>(),
+        #[cfg(feature = "static-data")]
+        None => static_data::similar_to(prefix)?.map(Zipcode::from).collect(),
+        #[cfg(not(feature = "static-data"))]
+        None => try_load()?.similar_to(prefix)?,
+    };
+    debug_println!("similar_to matched {:?} zipcodes for {}", similar_zipcodes.len(), prefix);
+    Ok(similar_zipcodes)
+}
+
+/// Borrow every zipcode in the database whose `zip_code` begins with the supplied prefix, sorted
+/// by `zip_code`.
+///
+/// This is the borrowing counterpart of `similar_to` when no override list is needed.
+pub fn similar_to_ref(prefix: &str) -> Result<&'static [Zipcode]> {
+    try_load()?.similar_to_ref(prefix)
 }
 
 /// Using a supplied list of filt-er-functions, return a filtered list of zipcodes.
 ///
+/// Every filter must be of the same type; to mix different kinds of conditions, use a `Query`.
+///
 /// By default, the supplied list of zipcodes is everything stored in the
 /// database. However, an optional list of override zipcodes can be supplied.
 pub fn filter_by<F>(filters: Vec<F>, zipcodes: Option<Vec<Zipcode>>) -> Result<Vec<Zipcode>>
                     where F: Fn(&Zipcode) -> bool {
-    let zipcodes = zipcodes.as_ref().unwrap_or(&ZIPCODES);
-    Ok(zipcodes.iter().filter(|z| {
-        for filter in &filters {
-            if !filter(z) {
-                return false;
-            }
+    match zipcodes {
+        Some(zipcodes) => Ok(zipcodes.into_iter().filter(|z| filters.iter().all(|f| f(z))).collect::<Vec<_>>()),
+        None => try_load()?.filter_by(filters),
+    }
+}
+
+/// Iterate over the zipcodes passing every supplied filter function, without cloning them.
+///
+/// This is the borrowing counterpart of `filter_by` when no override list is needed, and composes
+/// with the standard iterator adapters.
+pub fn filter_by_ref<'a, F>(filters: Vec<F>) -> Result<impl Iterator<Item = &'static Zipcode> + 'a>
+                            where F: Fn(&Zipcode) -> bool + 'a {
+    Ok(try_load()?.filter_by_ref(filters))
+}
+
+/// Return the zipcodes located in the supplied county of the supplied state, e.g. "Suffolk County"
+/// in `State::NewYork`. The county name is compared case-insensitively.
+///
+/// By default, the supplied list of zipcodes is everything stored in the
+/// database. However, an optional list of override zipcodes can be supplied.
+pub fn by_county(county: &str, state: State, zipcodes: Option<Vec<Zipcode>>) -> Result<Vec<Zipcode>> {
+    match zipcodes {
+        Some(zipcodes) => {
+            let county = county.trim();
+            Ok(zipcodes.into_iter().filter(|z| z.state == state && z.county.eq_ignore_ascii_case(county)).collect::<Vec<_>>())
         }
-        true
-    }).cloned().collect::<Vec<_>>())
+        None => Ok(try_load()?.by_county(county, state)),
+    }
+}
+
+/// List the distinct, non-empty county names in the supplied state, sorted alphabetically.
+pub fn counties(state: State) -> Result<Vec<&'static str>> {
+    Ok(try_load()?.counties(state))
 }
 
 /// Retrieve a list of all zipcodes in the database.
-pub fn list_all() -> Vec<Zipcode> {
-    ZIPCODES.clone()
+///
+/// This clones every record; use `iter` to borrow them instead.
+pub fn list_all() -> Result<Vec<Zipcode>> {
+    Ok(try_load()?.list_all())
+}
+
+/// Iterate over every zipcode in the database, sorted by `zip_code`, without cloning them.
+///
+/// This is the borrowing counterpart of `list_all`, and composes with the standard iterator
+/// adapters.
+pub fn iter() -> Result<std::slice::Iter<'static, Zipcode>> {
+    Ok(try_load()?.iter())
 }
 
-fn clean_zipcode(zipcode: &str) -> Result<&str> {
-    let zipcode = zipcode.trim();
-    if zipcode.len() < ZIPCODE_LENGTH {
-        return Err(Error::InvalidFormat);
+pub(crate) fn clean_prefix(prefix: &str) -> Result<&str> {
+    let prefix = prefix.trim();
+    if prefix.is_empty() || prefix.len() > ZIPCODE_LENGTH {
+        return Err(Error::InvalidPrefixLength);
     }
-    let split_zipcode = &zipcode[..ZIPCODE_LENGTH];
-    if !split_zipcode.chars().all(|c| c.is_numeric()) {
-        return Err(Error::InvalidCharacters);
+    if !prefix.chars().all(|c| c.is_ascii_digit()) {
+        return Err(Error::InvalidPrefixCharacters);
     }
-    Ok(zipcode)
+    Ok(prefix)
 }
 
 /// The available fields in the zipcode database.
@@ -98,32 +249,35 @@ fn clean_zipcode(zipcode: &str) -> Result<&str> {
 /// 'world_region': 'NA',
 /// 'zip_code': '77429',
 /// 'zip_code_type': 'STANDARD'}[
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Zipcode {
     pub acceptable_cities: Vec<String>,
     pub active: bool,
     pub area_codes: Vec<String>,
     pub city: String,
     pub country: String,
+    pub county: String,
     pub lat: String,
     pub long: String,
-    pub state: String,
+    pub state: State,
     pub timezone: String,
     pub unacceptable_cities: Vec<String>,
-    pub world_region: String,
+    pub world_region: WorldRegion,
     pub zip_code: String,
-    pub zip_code_type: String,
+    pub zip_code_type: ZipcodeType,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[cfg(any(feature = "bzip2", feature = "static-data"))]
     #[test]
     fn should_find_real_zipcodes() {
         assert!(is_real("06903").unwrap())
     }
 
+    #[cfg(any(feature = "bzip2", feature = "static-data"))]
     #[test]
     fn should_return_no_zipcodes() {
         for zc in &[
@@ -135,6 +289,7 @@ mod tests {
         }
     }
 
+    #[cfg(any(feature = "bzip2", feature = "static-data"))]
     #[test]
     fn should_fail_to_find_zipcodes_not_included_in_overrides() {
         let zc = "06903";
@@ -142,5 +297,89 @@ mod tests {
         assert!(matching(zc, Some(matching("06904", None).unwrap())).unwrap().is_empty());
     }
 
+    #[cfg(any(feature = "bzip2", feature = "static-data"))]
+    #[test]
+    fn should_borrow_zipcodes_from_the_index() {
+        let zipcode = get("77429").unwrap().unwrap();
+        assert_eq!(zipcode.city, "Cypress");
+        assert_eq!(matching_ref("77429").unwrap().len(), 1);
+        assert!(get("00000").unwrap().is_none());
+        assert!(matching_ref("00000").unwrap().is_empty());
+    }
+
+    #[cfg(any(feature = "bzip2", feature = "static-data"))]
+    #[test]
+    fn should_find_zipcodes_similar_to_prefix() {
+        let zipcodes = similar_to("1018", None).unwrap();
+        assert!(!zipcodes.is_empty());
+        assert!(zipcodes.iter().all(|z| z.zip_code.starts_with("1018")));
+        assert_eq!(similar_to_ref("1018").unwrap().len(), zipcodes.len());
+        assert_eq!(similar_to("77429", None).unwrap().len(), 1);
+    }
+
+    #[cfg(any(feature = "bzip2", feature = "static-data"))]
+    #[test]
+    fn should_iterate_without_cloning() {
+        assert_eq!(iter().unwrap().len(), list_all().unwrap().len());
+        let first = iter().unwrap().next().unwrap();
+        assert!(std::ptr::eq(first, try_load().unwrap().iter().next().unwrap()));
+
+        let city = String::from("Windsor");
+        let windsor = filter_by_ref(vec![|z: &Zipcode| z.active && z.city == city]).unwrap()
+            .filter(|z| z.zip_code.starts_with('2'))
+            .map(|z| z.zip_code.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(windsor, ["23487", "27983", "29856"]);
+    }
+
+    #[cfg(any(feature = "bzip2", feature = "static-data"))]
+    #[test]
+    fn should_find_similar_zipcodes_within_overrides() {
+        let windsor = filter_by(vec![|z: &Zipcode| z.active && z.city == "Windsor"], None).unwrap();
+        let zipcodes = similar_to("2", Some(windsor)).unwrap();
+        assert_eq!(zipcodes.iter().map(|z| z.zip_code.as_str()).collect::<Vec<_>>(), ["23487", "27983", "29856"]);
+    }
+
+    #[test]
+    fn should_reject_invalid_prefixes() {
+        assert!(matches!(similar_to("", None), Err(Error::InvalidPrefixLength)));
+        assert!(matches!(similar_to("123456", None), Err(Error::InvalidPrefixLength)));
+        assert!(matches!(similar_to("12a", None), Err(Error::InvalidPrefixCharacters)));
+    }
+
+    #[cfg(any(feature = "bzip2", feature = "static-data"))]
+    #[test]
+    fn should_match_zip_plus_four_zipcodes() {
+        for zc in &["77429-1145", "77429 1145", "774291145"] {
+            assert_eq!(matching(zc, None).unwrap()[0].zip_code, "77429");
+        }
+        let zipcode = "77429-1145".parse::<ZipPlus4>().unwrap();
+        assert!(is_real(zipcode).unwrap());
+        assert!(is_real(zipcode.zip5()).unwrap());
+        assert!(matches!(matching("12345abc", None), Err(Error::InvalidFormat)));
+        assert!(matches!(matching("123456789012", None), Err(Error::InvalidFormat)));
+    }
+
+    #[cfg(any(feature = "bzip2", feature = "static-data"))]
+    #[test]
+    fn should_find_zipcodes_by_county() {
+        let zipcodes = by_county("suffolk county", State::NewYork, None).unwrap();
+        assert!(zipcodes.iter().any(|z| z.zip_code == "00501"));
+        assert!(zipcodes.iter().all(|z| z.county == "Suffolk County" && z.state == State::NewYork));
+        assert!(by_county("Suffolk County", State::NewYork, Some(matching("77429", None).unwrap())).unwrap().is_empty());
+        assert!(counties(State::Texas).unwrap().contains(&"Harris County"));
+        assert!(!counties(State::NewYork).unwrap().contains(&"Harris County"));
+    }
+
+    #[cfg(any(feature = "bzip2", feature = "static-data"))]
+    #[test]
+    fn should_match_the_embedded_database() {
+        init().unwrap();
+        let db = ZipcodeDb::embedded().unwrap();
+        assert_eq!(db.len(), list_all().unwrap().len());
+        assert_eq!(db.matching("77429").unwrap().len(), matching("77429", None).unwrap().len());
+        assert!(db.is_real("06903").unwrap());
+    }
+
     // TODO: Migrate remaining unittests for the python library.
 }
@@ -0,0 +1,218 @@
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::address::normalize_state;
+use crate::{try_load, Error, Result, State, ToZip5, Zipcode, ZipcodeDb, ZipcodeType};
+
+/// The city of a military address, which names the kind of post office serving it rather than a
+/// place, and is used by every branch of the armed forces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum MilitaryPostOffice {
+    /// Army/Air Post Office, serving Army and Air Force installations.
+    #[serde(rename = "APO")]
+    Apo,
+    /// Fleet Post Office, serving Navy and Marine Corps installations and ships.
+    #[serde(rename = "FPO")]
+    Fpo,
+    /// Diplomatic Post Office, serving embassies and consulates.
+    #[serde(rename = "DPO")]
+    Dpo,
+}
+
+impl MilitaryPostOffice {
+    /// The city code as written on an address label.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MilitaryPostOffice::Apo => "APO",
+            MilitaryPostOffice::Fpo => "FPO",
+            MilitaryPostOffice::Dpo => "DPO",
+        }
+    }
+}
+
+impl fmt::Display for MilitaryPostOffice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for MilitaryPostOffice {
+    type Err = Error;
+
+    /// Parse a city code, ignoring case and punctuation, so that "A.P.O." is `Apo`.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_ascii_uppercase().as_str() {
+            "APO" => Ok(MilitaryPostOffice::Apo),
+            "FPO" => Ok(MilitaryPostOffice::Fpo),
+            "DPO" => Ok(MilitaryPostOffice::Dpo),
+            _ => Err(Error::UnknownVariant { kind: "MilitaryPostOffice", value: s.trim().to_string() }),
+        }
+    }
+}
+
+impl State {
+    /// Whether this is one of the "states" used for military addresses: AA, AE or AP.
+    pub fn is_military(&self) -> bool {
+        matches!(self, State::ArmedForcesAmericas | State::ArmedForcesEurope | State::ArmedForcesPacific)
+    }
+}
+
+/// How to address mail to a military zipcode, as returned by `military_info`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct MilitaryInfo<'a> {
+    pub zip_code: &'a str,
+    /// The military "state": AA, AE or AP.
+    pub state: State,
+    /// The city to write on the address: APO, FPO or DPO.
+    pub post_office: MilitaryPostOffice,
+    /// The country code of the host country as recorded in the database, such as "DE" or "KR",
+    /// if any. Zipcodes serving ships and mobile units are mostly recorded as "US".
+    pub country: Option<&'a str>,
+}
+
+/// A problem with the city, state and zipcode of a military address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MilitaryIssue {
+    /// The city is not APO, FPO or DPO.
+    InvalidCityCode,
+    /// The state is not AA, AE or AP.
+    InvalidState,
+    /// The zipcode does not exist, or is not a military zipcode.
+    UnknownZipcode,
+    /// The city code is valid, but not the one serving the zipcode, such as APO for an FPO
+    /// zipcode.
+    WrongCityCode,
+    /// The state is valid, but not the one the zipcode belongs to, such as AP for an AE
+    /// zipcode.
+    WrongState,
+}
+
+/// The result of `validate_military_address`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct MilitaryValidation<'a> {
+    /// Every problem found, in the order of `MilitaryIssue`'s variants. Empty if the address is
+    /// correct.
+    pub issues: Vec<MilitaryIssue>,
+    /// The correct city code and state for the zipcode, if it is a military zipcode.
+    pub expected: Option<MilitaryInfo<'a>>,
+}
+
+impl MilitaryValidation<'_> {
+    /// Whether the city code, state and zipcode are correctly paired.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl Zipcode {
+    /// How to address mail to the zipcode, or `None` if it is not a military zipcode.
+    pub fn military_info(&self) -> Option<MilitaryInfo<'_>> {
+        if self.zip_code_type != ZipcodeType::Military || !self.state.is_military() {
+            return None;
+        }
+        Some(MilitaryInfo {
+            zip_code: &self.zip_code,
+            state: self.state,
+            post_office: self.city.parse().ok()?,
+            country: Some(self.country.as_str()).filter(|c| !c.is_empty()),
+        })
+    }
+}
+
+impl ZipcodeDb {
+    /// How to address mail to a military zipcode, as in `crate::military_info`.
+    pub fn military_info<Z: ToZip5>(&self, zipcode: Z) -> Result<Option<MilitaryInfo<'_>>> {
+        Ok(self.get(zipcode)?.and_then(Zipcode::military_info))
+    }
+
+    /// Check the pairing of a military address, as in `crate::validate_military_address`.
+    pub fn validate_military_address<Z: ToZip5>(&self, city: &str, state: &str, zipcode: Z) -> Result<MilitaryValidation<'_>> {
+        let expected = self.military_info(zipcode)?;
+        let post_office = city.parse::<MilitaryPostOffice>().ok();
+        let state = normalize_state(state).filter(State::is_military);
+        let mut issues = Vec::new();
+        if post_office.is_none() {
+            issues.push(MilitaryIssue::InvalidCityCode);
+        }
+        if state.is_none() {
+            issues.push(MilitaryIssue::InvalidState);
+        }
+        match expected {
+            None => issues.push(MilitaryIssue::UnknownZipcode),
+            Some(expected) => {
+                if post_office.is_some_and(|p| p != expected.post_office) {
+                    issues.push(MilitaryIssue::WrongCityCode);
+                }
+                if state.is_some_and(|s| s != expected.state) {
+                    issues.push(MilitaryIssue::WrongState);
+                }
+            }
+        }
+        Ok(MilitaryValidation { issues, expected })
+    }
+}
+
+/// How to address mail to a military zipcode: its military "state" (AA, AE or AP), the city code
+/// of the post office serving it (APO, FPO or DPO) and the host country. Returns `None` if the
+/// zipcode does not exist or is not a military zipcode.
+///
+/// The supplied zipcode may be in any of the formats accepted by `matching`.
+pub fn military_info<Z: ToZip5>(zipcode: Z) -> Result<Option<MilitaryInfo<'static>>> {
+    try_load()?.military_info(zipcode)
+}
+
+/// Check that the city code, state and zipcode of a military address belong together, such as
+/// "FPO AP 96349", reporting every problem found along with the correct pairing.
+///
+/// The city must be APO, FPO or DPO and the state AA, AE or AP, or spelled out as in "Armed
+/// Forces Europe", both compared ignoring case and punctuation, and both must be the ones the
+/// database records for the zipcode.
+///
+/// The supplied zipcode may be in any of the formats accepted by `matching`.
+pub fn validate_military_address<Z: ToZip5>(city: &str, state: &str, zipcode: Z) -> Result<MilitaryValidation<'static>> {
+    try_load()?.validate_military_address(city, state, zipcode)
+}
+
+#[cfg(all(test, any(feature = "bzip2", feature = "static-data")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_describe_military_zipcodes() {
+        let info = military_info("09001").unwrap().unwrap();
+        assert_eq!((info.state, info.post_office), (State::ArmedForcesEurope, MilitaryPostOffice::Apo));
+
+        // Every military zipcode in the dataset has a military state and city code.
+        let military = crate::iter().unwrap().filter(|z| z.zip_code_type == ZipcodeType::Military);
+        assert!(military.clone().all(|z| z.military_info().is_some()));
+        assert_eq!(military.count(), 787);
+        assert!(crate::iter().unwrap().filter_map(Zipcode::military_info).any(|m| m.country == Some("KR")));
+
+        assert_eq!(military_info("77429").unwrap(), None);
+        assert_eq!(military_info("06463").unwrap(), None);
+        assert!(military_info("0646a").is_err());
+        assert_eq!(" a.p.o. ".parse::<MilitaryPostOffice>().unwrap(), MilitaryPostOffice::Apo);
+        assert_eq!(serde_json::to_string(&MilitaryPostOffice::Dpo).unwrap(), "\"DPO\"");
+    }
+
+    #[test]
+    fn should_validate_military_pairings() {
+        let issues = |city, state, zip| validate_military_address(city, state, zip).unwrap().issues;
+        assert!(validate_military_address("apo", "a.e.", "09001-1234").unwrap().is_valid());
+        assert!(validate_military_address("FPO", "AP", "96349").unwrap().is_valid());
+        assert_eq!(issues("FPO", "AE", "09001"), [MilitaryIssue::WrongCityCode]);
+        assert_eq!(issues("APO", "AP", "09001"), [MilitaryIssue::WrongState]);
+        assert_eq!(issues("Frankfurt", "AE", "09001"), [MilitaryIssue::InvalidCityCode]);
+        assert_eq!(issues("APO", "NY", "09001"), [MilitaryIssue::InvalidState]);
+        assert_eq!(issues("APO", "AE", "77429"), [MilitaryIssue::UnknownZipcode]);
+        assert_eq!(issues("Cypress", "TX", "77429"), [
+            MilitaryIssue::InvalidCityCode,
+            MilitaryIssue::InvalidState,
+            MilitaryIssue::UnknownZipcode,
+        ]);
+        let expected = validate_military_address("FPO", "AP", "09001").unwrap().expected;
+        assert_eq!(expected, military_info("09001").unwrap());
+    }
+}
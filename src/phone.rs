@@ -0,0 +1,141 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::{try_load, Error, Result, State, ToZip5, Zipcode, ZipcodeDb};
+
+/// The zipcodes and states served by a telephone area code, as returned by `by_area_code`.
+#[derive(Clone, Debug, Serialize)]
+pub struct ServiceArea<'a> {
+    /// Every zipcode whose `area_codes` include the area code, in order.
+    pub zipcodes: Vec<&'a Zipcode>,
+    /// The distinct states of those zipcodes, in the order of `State::ALL`.
+    pub states: Vec<State>,
+}
+
+impl ServiceArea<'_> {
+    /// Whether the area code serves no zipcodes in the database, as for toll-free and other
+    /// non-geographic area codes.
+    pub fn is_empty(&self) -> bool {
+        self.zipcodes.is_empty()
+    }
+}
+
+/// Build an index from each area code to the positions of the zipcodes it serves.
+pub(crate) fn index(zipcodes: &[Zipcode]) -> HashMap<String, Vec<usize>> {
+    let mut index = HashMap::<String, Vec<usize>>::new();
+    for (i, zipcode) in zipcodes.iter().enumerate() {
+        for area_code in &zipcode.area_codes {
+            index.entry(area_code.clone()).or_default().push(i);
+        }
+    }
+    index
+}
+
+impl ZipcodeDb {
+    /// The zipcodes and states served by an area code, as in `crate::by_area_code`.
+    pub fn by_area_code(&self, area_code: &str) -> Result<ServiceArea<'_>> {
+        let area_code = area_code.trim();
+        if !is_area_code(area_code.as_bytes()) {
+            return Err(Error::InvalidAreaCode(area_code.to_string()));
+        }
+        let zipcodes = self.area_code_index()
+            .get(area_code)
+            .map_or(Vec::new(), |positions| positions.iter().map(|&i| &self.zipcodes()[i]).collect::<Vec<_>>());
+        let mut states = zipcodes.iter().map(|z| z.state).collect::<Vec<_>>();
+        states.sort_unstable();
+        states.dedup();
+        Ok(ServiceArea { zipcodes, states })
+    }
+
+    /// Whether a phone number's area code serves a zipcode, as in `crate::phone_matches_zip`.
+    pub fn phone_matches_zip<Z: ToZip5>(&self, phone: &str, zipcode: Z) -> Result<Option<bool>> {
+        let area_code = phone_area_code(phone)?;
+        Ok(self.get(zipcode)?
+            .filter(|z| !z.area_codes.is_empty())
+            .map(|z| z.area_codes.contains(&area_code)))
+    }
+}
+
+/// The zipcodes and states served by a three digit telephone area code, such as "281" for
+/// Houston, found through an index built on first use.
+///
+/// An area code that is not three digits starting with 2 to 9 fails with
+/// `Error::InvalidAreaCode`. A valid one that serves no zipcodes returns an empty `ServiceArea`.
+pub fn by_area_code(area_code: &str) -> Result<ServiceArea<'static>> {
+    try_load()?.by_area_code(area_code)
+}
+
+/// Whether the area code of a phone number serves the supplied zipcode, for flagging customers
+/// whose phone and billing zipcode are far apart.
+///
+/// The phone number is parsed as in `phone_area_code`, and the zipcode may be in any of the
+/// formats accepted by `matching`. Returns `None` when there is nothing to compare against,
+/// because the zipcode does not exist or has no area codes in the database, as for most
+/// military zipcodes.
+pub fn phone_matches_zip<Z: ToZip5>(phone: &str, zipcode: Z) -> Result<Option<bool>> {
+    try_load()?.phone_matches_zip(phone, zipcode)
+}
+
+/// The area code of a North American Numbering Plan phone number, such as "281" for
+/// "(281) 555-1234", "281.555.1234", "1-281-555-1234" or "+1 281 555 1234".
+///
+/// The number may contain spaces, dashes, dots and parentheses between its digits, and fails
+/// with `Error::InvalidPhoneNumber` if it is not ten digits, optionally preceded by the country
+/// code 1, with an area code and exchange that both start with 2 to 9.
+pub fn phone_area_code(phone: &str) -> Result<String> {
+    let invalid = || Error::InvalidPhoneNumber(phone.to_string());
+    let (international, number) = match phone.trim().strip_prefix('+') {
+        Some(number) => (true, number),
+        None => (false, phone.trim()),
+    };
+    if !number.chars().all(|c| c.is_ascii_digit() || " -.()".contains(c)) {
+        return Err(invalid());
+    }
+    let digits = number.bytes().filter(u8::is_ascii_digit).collect::<Vec<_>>();
+    let national = match (international, digits.len()) {
+        (_, 11) if digits[0] == b'1' => &digits[1..],
+        (false, 10) => &digits[..],
+        _ => return Err(invalid()),
+    };
+    if !is_area_code(&national[..3]) || national[3] < b'2' {
+        return Err(invalid());
+    }
+    Ok(String::from_utf8_lossy(&national[..3]).into_owned())
+}
+
+fn is_area_code(code: &[u8]) -> bool {
+    matches!(code, [b'2'..=b'9', b'0'..=b'9', b'0'..=b'9'])
+}
+
+#[cfg(all(test, any(feature = "bzip2", feature = "static-data")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_look_up_zipcodes_by_area_code() {
+        let houston = by_area_code(" 281 ").unwrap();
+        assert_eq!(houston.states, [State::Texas]);
+        assert!(houston.zipcodes.iter().any(|z| z.zip_code == "77429"));
+        assert!(houston.zipcodes.iter().all(|z| z.area_codes.iter().any(|a| a == "281")));
+        assert!(houston.zipcodes.windows(2).all(|w| w[0].zip_code < w[1].zip_code));
+
+        assert!(by_area_code("800").unwrap().is_empty());
+        assert!(matches!(by_area_code("181"), Err(Error::InvalidAreaCode(_))));
+        assert!(matches!(by_area_code("28"), Err(Error::InvalidAreaCode(_))));
+    }
+
+    #[test]
+    fn should_match_phone_numbers_to_zipcodes() {
+        for phone in ["(281) 555-1234", "281.555.1234", "1-281-555-1234", "+1 281 555 1234", "+12815551234"] {
+            assert_eq!(phone_area_code(phone).unwrap(), "281", "{}", phone);
+        }
+        for phone in ["555-1234", "+44 20 7946 0958", "(281) 155-1234", "281-555-1234 x5", "+1 (081) 555-1234"] {
+            assert!(matches!(phone_area_code(phone), Err(Error::InvalidPhoneNumber(_))), "{}", phone);
+        }
+
+        assert_eq!(phone_matches_zip("(832) 555-1234", "77429-1145").unwrap(), Some(true));
+        assert_eq!(phone_matches_zip("(860) 555-1234", "77429").unwrap(), Some(false));
+        assert_eq!(phone_matches_zip("(860) 555-1234", "06463").unwrap(), None);
+        assert!(phone_matches_zip("(860) 555-1234", "0646a").is_err());
+    }
+}
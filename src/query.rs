@@ -0,0 +1,282 @@
+use std::fmt;
+use std::ops::Not;
+use std::sync::Arc;
+
+use crate::{clean_prefix, try_load, Result, State, Zip5, Zipcode, ZipcodeDb, ZipcodeType};
+
+/// A declarative filter over zipcodes, built up from field conditions that must all hold.
+///
+/// ```
+/// # #[cfg(any(feature = "bzip2", feature = "static-data"))] {
+/// use zipcodes::{Query, State, ZipcodeType};
+///
+/// let zipcodes = Query::new()
+///     .state(State::Texas)
+///     .city_ci("cypress")
+///     .active(true)
+///     .zip_type(ZipcodeType::Standard)
+///     .limit(10)
+///     .execute(None)
+///     .unwrap();
+/// assert!(zipcodes.iter().any(|z| z.zip_code == "77429"));
+/// # }
+/// ```
+///
+/// Queries combine with `and`, `or` and `!`, and `matches` accepts an arbitrary closure for
+/// anything the builder does not cover. When a query pins down a zipcode or a zipcode prefix,
+/// execution starts from the corresponding index instead of scanning the whole database.
+#[derive(Clone, Debug, Default)]
+pub struct Query {
+    condition: Condition,
+    limit: Option<usize>,
+}
+
+#[derive(Clone, Debug, Default)]
+enum Condition {
+    #[default]
+    Always,
+    ZipCode(Zip5),
+    Prefix(String),
+    State(State),
+    City(String),
+    CityCi(String),
+    County(String),
+    Active(bool),
+    ZipType(ZipcodeType),
+    AreaCode(String),
+    Matches(Predicate),
+    And(Vec<Condition>),
+    Or(Vec<Condition>),
+    Not(Box<Condition>),
+}
+
+#[derive(Clone)]
+struct Predicate(Arc<dyn Fn(&Zipcode) -> bool + Send + Sync>);
+
+impl fmt::Debug for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Predicate(..)")
+    }
+}
+
+impl Condition {
+    fn is_match(&self, zipcode: &Zipcode) -> bool {
+        match self {
+            Condition::Always => true,
+            Condition::ZipCode(zip5) => zipcode.zip_code == zip5.as_str(),
+            Condition::Prefix(prefix) => zipcode.zip_code.starts_with(prefix.trim()),
+            Condition::State(state) => zipcode.state == *state,
+            Condition::City(city) => zipcode.city == *city,
+            Condition::CityCi(city) => zipcode.city.eq_ignore_ascii_case(city),
+            Condition::County(county) => zipcode.county.eq_ignore_ascii_case(county),
+            Condition::Active(active) => zipcode.active == *active,
+            Condition::ZipType(zip_type) => zipcode.zip_code_type == *zip_type,
+            Condition::AreaCode(area_code) => zipcode.area_codes.contains(area_code),
+            Condition::Matches(predicate) => (predicate.0)(zipcode),
+            Condition::And(conditions) => conditions.iter().all(|c| c.is_match(zipcode)),
+            Condition::Or(conditions) => conditions.iter().any(|c| c.is_match(zipcode)),
+            Condition::Not(condition) => !condition.is_match(zipcode),
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        match self {
+            Condition::Prefix(prefix) => clean_prefix(prefix).map(|_| ()),
+            Condition::And(conditions) | Condition::Or(conditions) => conditions.iter().try_for_each(Condition::validate),
+            Condition::Not(condition) => condition.validate(),
+            _ => Ok(()),
+        }
+    }
+
+    /// The smallest indexed candidate set that every match must belong to, if the condition
+    /// requires a specific zipcode or prefix.
+    fn candidates<'a>(&self, db: &'a ZipcodeDb) -> Option<&'a [Zipcode]> {
+        match self {
+            Condition::ZipCode(zip5) => Some(db.lookup(*zip5)),
+            Condition::Prefix(prefix) => Some(db.prefix_range(prefix.trim())),
+            Condition::And(conditions) => conditions.iter().filter_map(|c| c.candidates(db)).min_by_key(|c| c.len()),
+            _ => None,
+        }
+    }
+
+    fn and(self, other: Condition) -> Condition {
+        match (self, other) {
+            (Condition::Always, other) | (other, Condition::Always) => other,
+            (Condition::And(mut conditions), other) => {
+                conditions.push(other);
+                Condition::And(conditions)
+            }
+            (condition, other) => Condition::And(vec![condition, other]),
+        }
+    }
+}
+
+impl Query {
+    /// Create a query that matches every zipcode.
+    pub fn new() -> Self {
+        Query::default()
+    }
+
+    fn with(mut self, condition: Condition) -> Self {
+        self.condition = self.condition.and(condition);
+        self
+    }
+
+    /// Only match the supplied 5-digit zipcode.
+    pub fn zip_code(self, zipcode: Zip5) -> Self {
+        self.with(Condition::ZipCode(zipcode))
+    }
+
+    /// Only match zipcodes beginning with the supplied prefix of 1 to 5 digits.
+    pub fn prefix(self, prefix: &str) -> Self {
+        self.with(Condition::Prefix(prefix.to_string()))
+    }
+
+    /// Only match zipcodes in the supplied state.
+    pub fn state(self, state: State) -> Self {
+        self.with(Condition::State(state))
+    }
+
+    /// Only match zipcodes whose preferred city name is exactly `city`.
+    pub fn city(self, city: &str) -> Self {
+        self.with(Condition::City(city.to_string()))
+    }
+
+    /// Only match zipcodes whose preferred city name is `city`, ignoring case.
+    pub fn city_ci(self, city: &str) -> Self {
+        self.with(Condition::CityCi(city.to_string()))
+    }
+
+    /// Only match zipcodes in the supplied county, ignoring case.
+    pub fn county(self, county: &str) -> Self {
+        self.with(Condition::County(county.to_string()))
+    }
+
+    /// Only match active or only match inactive zipcodes.
+    pub fn active(self, active: bool) -> Self {
+        self.with(Condition::Active(active))
+    }
+
+    /// Only match zipcodes of the supplied type.
+    pub fn zip_type(self, zip_type: ZipcodeType) -> Self {
+        self.with(Condition::ZipType(zip_type))
+    }
+
+    /// Only match zipcodes served by the supplied telephone area code.
+    pub fn area_code(self, area_code: &str) -> Self {
+        self.with(Condition::AreaCode(area_code.to_string()))
+    }
+
+    /// Only match zipcodes for which the supplied closure returns true.
+    pub fn matches<F>(self, predicate: F) -> Self
+    where
+        F: Fn(&Zipcode) -> bool + Send + Sync + 'static,
+    {
+        self.with(Condition::Matches(Predicate(Arc::new(predicate))))
+    }
+
+    /// Only match zipcodes matched by both this query and `other`.
+    pub fn and(self, other: Query) -> Self {
+        self.with(other.condition)
+    }
+
+    /// Match zipcodes matched by either this query or `other`.
+    pub fn or(self, other: Query) -> Self {
+        Query {
+            condition: Condition::Or(vec![self.condition, other.condition]),
+            limit: self.limit,
+        }
+    }
+
+    /// Return at most `limit` zipcodes when executed.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Whether the supplied zipcode satisfies the query, ignoring any limit.
+    pub fn is_match(&self, zipcode: &Zipcode) -> bool {
+        self.condition.is_match(zipcode)
+    }
+
+    /// Run the query, returning the matching zipcodes in database order.
+    ///
+    /// By default, the supplied list of zipcodes is everything stored in the
+    /// database. However, an optional list of override zipcodes can be supplied.
+    pub fn execute(&self, zipcodes: Option<Vec<Zipcode>>) -> Result<Vec<Zipcode>> {
+        match zipcodes {
+            Some(zipcodes) => {
+                self.condition.validate()?;
+                let limit = self.limit.unwrap_or(usize::MAX);
+                Ok(zipcodes.into_iter().filter(|z| self.is_match(z)).take(limit).collect())
+            }
+            None => Ok(self.execute_ref()?.into_iter().cloned().collect()),
+        }
+    }
+
+    /// Run the query against the database, borrowing the matching zipcodes.
+    ///
+    /// This is the borrowing counterpart of `execute` when no override list is needed.
+    pub fn execute_ref(&self) -> Result<Vec<&'static Zipcode>> {
+        try_load()?.query(self)
+    }
+}
+
+impl ZipcodeDb {
+    /// Run the supplied query against the database, borrowing the matching zipcodes, as in
+    /// `Query::execute_ref`.
+    pub fn query(&self, query: &Query) -> Result<Vec<&Zipcode>> {
+        query.condition.validate()?;
+        let candidates = query.condition.candidates(self).unwrap_or(self.zipcodes());
+        let limit = query.limit.unwrap_or(usize::MAX);
+        Ok(candidates.iter().filter(|z| query.is_match(z)).take(limit).collect())
+    }
+}
+
+impl Not for Query {
+    type Output = Query;
+
+    /// Match exactly the zipcodes this query does not.
+    fn not(self) -> Query {
+        Query {
+            condition: Condition::Not(Box::new(self.condition)),
+            limit: self.limit,
+        }
+    }
+}
+
+#[cfg(all(test, any(feature = "bzip2", feature = "static-data")))]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn should_combine_conditions() {
+        let cypress = Query::new().state(State::Texas).city_ci("CYPRESS");
+        let zipcodes = cypress.clone().active(true).zip_type(ZipcodeType::Standard).execute_ref().unwrap();
+        assert!(zipcodes.iter().any(|z| z.zip_code == "77429"));
+        assert!(zipcodes.iter().all(|z| z.active && z.city == "Cypress" && z.zip_code_type == ZipcodeType::Standard));
+
+        let either = cypress.clone().or(Query::new().city("Old Saybrook")).execute_ref().unwrap();
+        assert!(either.iter().any(|z| z.zip_code == "06475"));
+        assert!(either.iter().any(|z| z.zip_code == "77429"));
+
+        let not_standard = cypress.clone().and(!Query::new().zip_type(ZipcodeType::Standard)).execute_ref().unwrap();
+        assert!(!not_standard.is_empty());
+        assert!(not_standard.iter().all(|z| z.zip_code_type != ZipcodeType::Standard));
+
+        assert_eq!(Query::new().limit(3).execute_ref().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn should_use_indexes_and_closures() {
+        let zip5 = "77429".parse::<Zip5>().unwrap();
+        let query = Query::new().zip_code(zip5).matches(|z| z.area_codes.len() > 1);
+        assert_eq!(query.execute(None).unwrap().len(), 1);
+        assert!(query.execute(Some(vec![])).unwrap().is_empty());
+
+        let windsor = Query::new().prefix("2").city("Windsor").active(true).execute_ref().unwrap();
+        assert_eq!(windsor.iter().map(|z| z.zip_code.as_str()).collect::<Vec<_>>(), ["23487", "27983", "29856"]);
+        assert!(matches!(Query::new().prefix("2a").execute_ref(), Err(Error::InvalidPrefixCharacters)));
+    }
+}
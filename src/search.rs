@@ -0,0 +1,164 @@
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::address::normalize_city;
+use crate::{try_load, Result, State, Zipcode, ZipcodeDb};
+
+/// The lowest score `search_city` returns, below which names have too little in common with the
+/// query to be a typo of it.
+const MIN_SCORE: f64 = 0.6;
+
+/// Abbreviations expanded before comparing city names, so that "St. Louis" matches "Saint Louis".
+const ABBREVIATIONS: &[(&str, &str)] = &[("st", "saint"), ("ste", "sainte"), ("ft", "fort"), ("mt", "mount")];
+
+/// A zipcode found by `search_city`.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct CityMatch<'a> {
+    pub zipcode: &'a Zipcode,
+    /// The city name that matched: the preferred `city`, or one of the `acceptable_cities`.
+    pub name: &'a str,
+    /// How closely the name matched the query, from 1 for an exact match down to 0.
+    pub score: f64,
+}
+
+impl ZipcodeDb {
+    /// Search for zipcodes by city name, tolerating typos, as in `crate::search_city`.
+    pub fn search_city(&self, query: &str, state: Option<&str>, limit: usize) -> Result<Vec<CityMatch<'_>>> {
+        let state = state.map(str::parse::<State>).transpose()?;
+        let query = normalize_city(query);
+        // Without a state, a trailing state code may either restrict the search, as in "Cyprus TX",
+        // or be part of the city name, as in "Lake La". Both readings are scored, and each
+        // zipcode keeps the better.
+        let trailing = query.rsplit_once(' ')
+            .filter(|(_, code)| state.is_none() && code.len() == 2)
+            .and_then(|(city, code)| Some((expand_abbreviations(city), code.parse::<State>().ok()?)));
+        let query = expand_abbreviations(&query);
+
+        let mut scores = HashMap::new();
+        let mut score = |name: &str| -> (f64, f64) {
+            *scores.entry(name.to_string()).or_insert_with(|| {
+                let name = normalize_name(name);
+                (similarity(&query, &name), trailing.as_ref().map_or(0.0, |(city, _)| similarity(city, &name)))
+            })
+        };
+        let mut matches = Vec::new();
+        for zipcode in self.zipcodes().iter().filter(|z| state.is_none_or(|s| z.state == s)) {
+            let in_state = trailing.as_ref().is_some_and(|(_, s)| *s == zipcode.state);
+            let best = std::iter::once(&zipcode.city)
+                .chain(&zipcode.acceptable_cities)
+                .map(|name| {
+                    let (whole, without_state) = score(name);
+                    (name.as_str(), if in_state { whole.max(without_state) } else { whole })
+                })
+                .fold(None, |best: Option<(&str, f64)>, (name, score)| match best {
+                    Some((_, best_score)) if best_score >= score => best,
+                    _ => Some((name, score)),
+                });
+            if let Some((name, score)) = best.filter(|(_, score)| *score >= MIN_SCORE) {
+                matches.push(CityMatch { zipcode, name, score });
+            }
+        }
+        matches.sort_by(|a, b| {
+            b.score.partial_cmp(&a.score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| (b.name == b.zipcode.city).cmp(&(a.name == a.zipcode.city)))
+                .then_with(|| b.zipcode.active.cmp(&a.zipcode.active))
+                .then_with(|| a.zipcode.zip_code.cmp(&b.zipcode.zip_code))
+        });
+        matches.truncate(limit);
+        Ok(matches)
+    }
+}
+
+/// Search for zipcodes by city name, returning at most `limit` matches, best first.
+///
+/// Each zipcode is scored by the normalized edit distance between the query and the closest of
+/// its preferred and acceptable city names, ignoring case and punctuation and treating
+/// abbreviations like "St." and "Ft." as "Saint" and "Fort". Ties go to preferred names, then to
+/// active zipcodes. The search can be restricted to a state with `state`. Without one, a query
+/// ending with a state code, as in "Cyprus TX", also matches the city in that state, while still
+/// matching city names that end with the same letters, as in "Lake La".
+pub fn search_city(query: &str, state: Option<&str>, limit: usize) -> Result<Vec<CityMatch<'static>>> {
+    try_load()?.search_city(query, state, limit)
+}
+
+fn normalize_name(name: &str) -> String {
+    expand_abbreviations(&normalize_city(name))
+}
+
+/// Expand the abbreviated words of a name normalized with `normalize_city`.
+fn expand_abbreviations(name: &str) -> String {
+    name.split(' ')
+        .map(|word| ABBREVIATIONS.iter().find(|(short, _)| *short == word).map_or(word, |(_, long)| long))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// One minus the edit distance between two strings, relative to the length of the longer.
+fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (a.chars().collect::<Vec<_>>(), b.chars().collect::<Vec<_>>());
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    // The distance is at least the difference in length, so skip names that cannot qualify.
+    if (a.len().abs_diff(b.len()) as f64) > (1.0 - MIN_SCORE) * longest as f64 {
+        return 0.0;
+    }
+    1.0 - levenshtein(&a, &b) as f64 / longest as f64
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(all(test, any(feature = "bzip2", feature = "static-data")))]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn should_tolerate_typos() {
+        let cypress = search_city("Cyprus TX", None, 3).unwrap();
+        assert!(cypress.iter().all(|m| m.zipcode.state == State::Texas && m.name == "Cypress"));
+        assert!(cypress.iter().any(|m| m.zipcode.zip_code == "77429"));
+
+        let old_saybrook = search_city("old saybrok", Some("ct"), 1).unwrap();
+        assert_eq!(old_saybrook[0].zipcode.zip_code, "06475");
+        assert!(old_saybrook[0].score > 0.9 && old_saybrook[0].score < 1.0);
+
+        let askewville = search_city("Askewvile", Some("NC"), 1).unwrap();
+        assert_eq!((askewville[0].name, askewville[0].zipcode.city.as_str()), ("Askewville", "Windsor"));
+
+        assert!(matches!(search_city("Cypress", Some("XX"), 1), Err(Error::UnknownVariant { kind: "State", .. })));
+        assert!(search_city("Qwxzv", None, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn should_match_city_names_ending_like_state_codes() {
+        for (query, zip_code) in [("Lake La", "93535"), ("Shawnee On De", "18356"), ("Dardenne Pr", "63366")] {
+            let found = search_city(query, None, 1).unwrap();
+            assert_eq!((found[0].name, found[0].zipcode.zip_code.as_str(), found[0].score), (query, zip_code, 1.0));
+        }
+    }
+
+    #[test]
+    fn should_expand_abbreviations() {
+        let st_louis = search_city("St. Louis", Some("MO"), 5).unwrap();
+        assert!(!st_louis.is_empty());
+        assert!(st_louis.iter().all(|m| m.score == 1.0 && normalize_name(m.name) == "saint louis"));
+        assert_eq!(search_city("Helena MT", None, 1).unwrap()[0].zipcode.state, State::Montana);
+        assert_eq!(levenshtein(&['k', 'i', 't', 't', 'e', 'n'], &['s', 'i', 't', 't', 'i', 'n', 'g']), 3);
+    }
+}
@@ -0,0 +1,179 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::Coordinates;
+
+/// A point on the unit sphere. Straight-line (chord) distance between two such points grows
+/// monotonically with their great-circle distance, so the tree can work in plain Euclidean space
+/// without special cases for the poles or the antimeridian.
+pub(crate) type Point = [f64; 3];
+
+pub(crate) fn to_point(coordinates: &Coordinates) -> Point {
+    let (lat, long) = (coordinates.lat.to_radians(), coordinates.long.to_radians());
+    [lat.cos() * long.cos(), lat.cos() * long.sin(), lat.sin()]
+}
+
+/// The chord length on the unit sphere spanning the supplied central angle, in radians.
+pub(crate) fn chord_length(angle: f64) -> f64 {
+    if angle >= std::f64::consts::PI {
+        2.0
+    } else {
+        2.0 * (angle / 2.0).sin()
+    }
+}
+
+fn squared_distance(a: &Point, b: &Point) -> f64 {
+    a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum()
+}
+
+/// A static k-d tree, stored as a flat array where every subrange `lo..hi` has its splitting node
+/// at the midpoint and splits on axis `depth % 3`.
+pub(crate) struct KdTree<T> {
+    nodes: Vec<(Point, T)>,
+}
+
+impl<T> KdTree<T> {
+    pub(crate) fn new(mut nodes: Vec<(Point, T)>) -> Self {
+        let len = nodes.len();
+        build(&mut nodes, 0, len, 0);
+        KdTree { nodes }
+    }
+
+    /// Every item within the supplied chord length of `center`, in no particular order.
+    pub(crate) fn within(&self, center: &Point, chord: f64) -> Vec<&T> {
+        let mut found = Vec::new();
+        self.search_within(center, chord * chord, 0, self.nodes.len(), 0, &mut found);
+        found
+    }
+
+    /// The `k` items closest to `center` that satisfy `accept`, closest first, along with their
+    /// squared chord distance.
+    pub(crate) fn nearest<F>(&self, center: &Point, k: usize, accept: F) -> Vec<(f64, &T)>
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        if k > 0 {
+            self.search_nearest(center, k, &accept, 0, self.nodes.len(), 0, &mut heap);
+        }
+        heap.into_sorted_vec().into_iter().map(|c| (c.distance, &self.nodes[c.index].1)).collect()
+    }
+
+    fn search_within<'a>(&'a self, center: &Point, max: f64, lo: usize, hi: usize, depth: usize, found: &mut Vec<&'a T>) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let (point, item) = &self.nodes[mid];
+        if squared_distance(center, point) <= max {
+            found.push(item);
+        }
+        let diff = center[depth % 3] - point[depth % 3];
+        let (near, far) = if diff <= 0.0 { ((lo, mid), (mid + 1, hi)) } else { ((mid + 1, hi), (lo, mid)) };
+        self.search_within(center, max, near.0, near.1, depth + 1, found);
+        if diff * diff <= max {
+            self.search_within(center, max, far.0, far.1, depth + 1, found);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn search_nearest<F>(&self, center: &Point, k: usize, accept: &F, lo: usize, hi: usize, depth: usize, heap: &mut BinaryHeap<Candidate>)
+    where
+        F: Fn(&T) -> bool,
+    {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let (point, item) = &self.nodes[mid];
+        if accept(item) {
+            let distance = squared_distance(center, point);
+            if heap.len() < k {
+                heap.push(Candidate { distance, index: mid });
+            } else if heap.peek().is_some_and(|worst| distance < worst.distance) {
+                heap.pop();
+                heap.push(Candidate { distance, index: mid });
+            }
+        }
+        let diff = center[depth % 3] - point[depth % 3];
+        let (near, far) = if diff <= 0.0 { ((lo, mid), (mid + 1, hi)) } else { ((mid + 1, hi), (lo, mid)) };
+        self.search_nearest(center, k, accept, near.0, near.1, depth + 1, heap);
+        if heap.len() < k || heap.peek().is_none_or(|worst| diff * diff < worst.distance) {
+            self.search_nearest(center, k, accept, far.0, far.1, depth + 1, heap);
+        }
+    }
+}
+
+fn build<T>(nodes: &mut [(Point, T)], lo: usize, hi: usize, depth: usize) {
+    if hi - lo <= 1 {
+        return;
+    }
+    let mid = (lo + hi) / 2;
+    let axis = depth % 3;
+    nodes[lo..hi].select_nth_unstable_by(mid - lo, |a, b| a.0[axis].total_cmp(&b.0[axis]));
+    build(nodes, lo, mid, depth + 1);
+    build(nodes, mid + 1, hi, depth + 1);
+}
+
+/// A node in the running k-nearest result set, ordered by distance so that the heap's top is the
+/// worst candidate found so far.
+struct Candidate {
+    distance: f64,
+    index: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance.total_cmp(&other.distance).then(self.index.cmp(&other.index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> KdTree<(i32, i32)> {
+        let mut nodes = Vec::new();
+        for lat in -10..=10 {
+            for long in -10..=10 {
+                let coordinates = Coordinates { lat: lat as f64, long: long as f64 };
+                nodes.push((to_point(&coordinates), (lat, long)));
+            }
+        }
+        KdTree::new(nodes)
+    }
+
+    #[test]
+    fn should_match_brute_force_search() {
+        let tree = grid();
+        let center = to_point(&Coordinates { lat: 0.2, long: 0.3 });
+        let chord = chord_length(2.5_f64.to_radians());
+        let mut found = tree.within(&center, chord).into_iter().copied().collect::<Vec<_>>();
+        let mut expected = tree.nodes.iter()
+            .filter(|(p, _)| squared_distance(p, &center) <= chord * chord)
+            .map(|(_, item)| *item)
+            .collect::<Vec<_>>();
+        found.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(found, expected);
+
+        let nearest = tree.nearest(&center, 3, |_| true);
+        assert_eq!(nearest.iter().map(|(_, item)| **item).collect::<Vec<_>>(), [(0, 0), (0, 1), (1, 0)]);
+        let nearest = tree.nearest(&center, 1, |(lat, _)| *lat < 0);
+        assert_eq!(*nearest[0].1, (-1, 0));
+    }
+}
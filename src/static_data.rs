@@ -0,0 +1,332 @@
+//! The embedded dataset compiled into fixed-width static tables by the build script, available
+//! with the `static-data` feature.
+//!
+//! Lookups here read straight from the tables, with no decompression, parsing or heap
+//! allocation, and return `StaticZipcode` handles whose fields borrow from the binary. The
+//! top-level `zipcodes::is_real`, and `zipcodes::matching` and `zipcodes::similar_to` without an
+//! override list, go through these lookups too, copying only the records they return. Everything
+//! else still copies every record into an owned `Zipcode` and builds its indexes on first use,
+//! though without any bzip2 decompression or JSON parsing.
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::convert::TryInto;
+use std::iter::FusedIterator;
+use std::ops::Range;
+
+use crate::{clean_prefix, Result, State, ToZip5, WorldRegion, Zipcode, ZipcodeType};
+
+// The layout of the tables is described in `build.rs`, which writes them.
+static STRINGS: &str = include_str!(concat!(env!("OUT_DIR"), "/strings.txt"));
+static LISTS: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/lists.bin"));
+static RECORDS: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/records.bin"));
+
+const SPAN_SIZE: usize = 6;
+const ZIP_CODE: usize = 0;
+const CITY: usize = 1;
+const COUNTRY: usize = 2;
+const COUNTY: usize = 3;
+const LAT: usize = 4;
+const LONG: usize = 5;
+const TIMEZONE: usize = 6;
+const AREA_CODES: usize = 7;
+const ACCEPTABLE_CITIES: usize = 8;
+const UNACCEPTABLE_CITIES: usize = 9;
+const ACTIVE: usize = 10 * SPAN_SIZE;
+const STATE: usize = ACTIVE + 1;
+const WORLD_REGION: usize = ACTIVE + 2;
+const ZIP_CODE_TYPE: usize = ACTIVE + 3;
+const RECORD_SIZE: usize = ACTIVE + 4;
+
+/// A little-endian u32 offset and u16 length.
+fn span(bytes: &[u8]) -> Range<usize> {
+    let offset = u32::from_le_bytes(bytes[..4].try_into().unwrap()) as usize;
+    let len = u16::from_le_bytes(bytes[4..SPAN_SIZE].try_into().unwrap()) as usize;
+    offset..offset + len
+}
+
+fn string(bytes: &[u8]) -> &'static str {
+    &STRINGS[span(bytes)]
+}
+
+/// A zipcode record in the static tables, with the same fields as `Zipcode` exposed as methods.
+#[derive(Clone, Copy)]
+pub struct StaticZipcode {
+    record: &'static [u8],
+}
+
+impl StaticZipcode {
+    fn string(&self, field: usize) -> &'static str {
+        string(&self.record[field * SPAN_SIZE..])
+    }
+
+    fn list(&self, field: usize) -> StaticList {
+        let entries = span(&self.record[field * SPAN_SIZE..]);
+        StaticList { entries: &LISTS[entries.start * SPAN_SIZE..entries.end * SPAN_SIZE] }
+    }
+
+    pub fn acceptable_cities(&self) -> StaticList {
+        self.list(ACCEPTABLE_CITIES)
+    }
+
+    pub fn active(&self) -> bool {
+        self.record[ACTIVE] == 1
+    }
+
+    pub fn area_codes(&self) -> StaticList {
+        self.list(AREA_CODES)
+    }
+
+    pub fn city(&self) -> &'static str {
+        self.string(CITY)
+    }
+
+    pub fn country(&self) -> &'static str {
+        self.string(COUNTRY)
+    }
+
+    pub fn county(&self) -> &'static str {
+        self.string(COUNTY)
+    }
+
+    pub fn lat(&self) -> &'static str {
+        self.string(LAT)
+    }
+
+    pub fn long(&self) -> &'static str {
+        self.string(LONG)
+    }
+
+    pub fn state(&self) -> State {
+        State::ALL[usize::from(self.record[STATE])]
+    }
+
+    pub fn timezone(&self) -> &'static str {
+        self.string(TIMEZONE)
+    }
+
+    pub fn unacceptable_cities(&self) -> StaticList {
+        self.list(UNACCEPTABLE_CITIES)
+    }
+
+    pub fn world_region(&self) -> WorldRegion {
+        WorldRegion::ALL[usize::from(self.record[WORLD_REGION])]
+    }
+
+    pub fn zip_code(&self) -> &'static str {
+        self.string(ZIP_CODE)
+    }
+
+    pub fn zip_code_type(&self) -> ZipcodeType {
+        ZipcodeType::ALL[usize::from(self.record[ZIP_CODE_TYPE])]
+    }
+
+    /// Copy the record into an owned `Zipcode`.
+    pub fn to_zipcode(&self) -> Zipcode {
+        let strings = |list: StaticList| list.map(str::to_string).collect();
+        Zipcode {
+            acceptable_cities: strings(self.acceptable_cities()),
+            active: self.active(),
+            area_codes: strings(self.area_codes()),
+            city: self.city().to_string(),
+            country: self.country().to_string(),
+            county: self.county().to_string(),
+            lat: self.lat().to_string(),
+            long: self.long().to_string(),
+            state: self.state(),
+            timezone: self.timezone().to_string(),
+            unacceptable_cities: strings(self.unacceptable_cities()),
+            world_region: self.world_region(),
+            zip_code: self.zip_code().to_string(),
+            zip_code_type: self.zip_code_type(),
+        }
+    }
+}
+
+impl From<StaticZipcode> for Zipcode {
+    fn from(zipcode: StaticZipcode) -> Self {
+        zipcode.to_zipcode()
+    }
+}
+
+impl std::fmt::Debug for StaticZipcode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("StaticZipcode").field(&self.zip_code()).finish()
+    }
+}
+
+impl Serialize for StaticZipcode {
+    /// Serialize exactly as the equivalent `Zipcode` would be.
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Zipcode", 14)?;
+        s.serialize_field("acceptable_cities", &self.acceptable_cities())?;
+        s.serialize_field("active", &self.active())?;
+        s.serialize_field("area_codes", &self.area_codes())?;
+        s.serialize_field("city", self.city())?;
+        s.serialize_field("country", self.country())?;
+        s.serialize_field("county", self.county())?;
+        s.serialize_field("lat", self.lat())?;
+        s.serialize_field("long", self.long())?;
+        s.serialize_field("state", &self.state())?;
+        s.serialize_field("timezone", self.timezone())?;
+        s.serialize_field("unacceptable_cities", &self.unacceptable_cities())?;
+        s.serialize_field("world_region", &self.world_region())?;
+        s.serialize_field("zip_code", self.zip_code())?;
+        s.serialize_field("zip_code_type", &self.zip_code_type())?;
+        s.end()
+    }
+}
+
+/// The strings of a list field of a `StaticZipcode`.
+#[derive(Clone, Debug)]
+pub struct StaticList {
+    entries: &'static [u8],
+}
+
+impl Iterator for StaticList {
+    type Item = &'static str;
+
+    fn next(&mut self) -> Option<&'static str> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let (entry, rest) = self.entries.split_at(SPAN_SIZE);
+        self.entries = rest;
+        Some(string(entry))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.entries.len() / SPAN_SIZE;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for StaticList {}
+
+impl FusedIterator for StaticList {}
+
+impl Serialize for StaticList {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.clone())
+    }
+}
+
+/// A run of consecutive records in the static tables, sorted by `zip_code`.
+#[derive(Clone, Debug)]
+pub struct StaticZipcodes {
+    records: Range<usize>,
+}
+
+impl Iterator for StaticZipcodes {
+    type Item = StaticZipcode;
+
+    fn next(&mut self) -> Option<StaticZipcode> {
+        self.records.next().map(record)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.records.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for StaticZipcodes {
+    fn next_back(&mut self) -> Option<StaticZipcode> {
+        self.records.next_back().map(record)
+    }
+}
+
+impl ExactSizeIterator for StaticZipcodes {}
+
+impl FusedIterator for StaticZipcodes {}
+
+fn record(index: usize) -> StaticZipcode {
+    StaticZipcode { record: &RECORDS[index * RECORD_SIZE..(index + 1) * RECORD_SIZE] }
+}
+
+fn len() -> usize {
+    RECORDS.len() / RECORD_SIZE
+}
+
+/// Every record in the tables, sorted by `zip_code`.
+pub fn all() -> StaticZipcodes {
+    StaticZipcodes { records: 0..len() }
+}
+
+/// The records matching the supplied zipcode, in the formats accepted by `crate::matching`.
+pub fn matching<Z: ToZip5>(zipcode: Z) -> Result<StaticZipcodes> {
+    Ok(prefix_range(zipcode.to_zip5()?.as_str()))
+}
+
+/// The first record matching the supplied zipcode, as in `crate::get`.
+pub fn get<Z: ToZip5>(zipcode: Z) -> Result<Option<StaticZipcode>> {
+    Ok(matching(zipcode)?.next())
+}
+
+/// Returns true if the supplied zipcode is a valid zipcode, as in `crate::is_real`.
+pub fn is_real<Z: ToZip5>(zipcode: Z) -> Result<bool> {
+    Ok(matching(zipcode)?.len() > 0)
+}
+
+/// The records whose `zip_code` begins with the supplied prefix of 1 to 5 digits, as in
+/// `crate::similar_to_ref`.
+pub fn similar_to(prefix: &str) -> Result<StaticZipcodes> {
+    Ok(prefix_range(clean_prefix(prefix)?))
+}
+
+fn prefix_range(prefix: &str) -> StaticZipcodes {
+    let start = partition_point(0, |z| z < prefix);
+    let end = partition_point(start, |z| z.starts_with(prefix));
+    StaticZipcodes { records: start..end }
+}
+
+/// The index of the first record from `low` onwards whose `zip_code` fails the predicate, which
+/// must hold for a prefix of the records, as in `slice::partition_point`.
+fn partition_point<P: Fn(&str) -> bool>(mut low: usize, pred: P) -> usize {
+    let mut high = len();
+    while low < high {
+        let mid = low + (high - low) / 2;
+        match pred(record(mid).zip_code()) {
+            true => low = mid + 1,
+            false => high = mid,
+        }
+    }
+    low
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn should_look_up_without_parsing() {
+        let cypress = get("77429-1145").unwrap().unwrap();
+        assert_eq!((cypress.city(), cypress.state()), ("Cypress", State::Texas));
+        assert_eq!(cypress.area_codes().collect::<Vec<_>>(), ["281", "832"]);
+        assert!(is_real("06469").unwrap());
+        assert!(!is_real("06463").unwrap());
+        assert_eq!(similar_to("1018").unwrap().map(|z| z.zip_code()).collect::<Vec<_>>(), ["10184", "10185"]);
+        assert!(matches!(similar_to("1a"), Err(Error::InvalidPrefixCharacters)));
+    }
+
+    #[cfg(feature = "bzip2")]
+    #[test]
+    fn should_match_the_compressed_dataset() {
+        let bzip2 = crate::ZipcodeDb::from_path("src/zips.json.bz2").unwrap();
+        assert_eq!(all().len(), bzip2.len());
+        for (record, zipcode) in all().zip(bzip2.iter()) {
+            assert_eq!(serde_json::to_value(record).unwrap(), serde_json::to_value(zipcode).unwrap());
+        }
+    }
+
+    #[test]
+    fn should_back_the_top_level_lookups() {
+        let db = crate::try_load().unwrap();
+        for zipcode in ["77429", "06463", "96349", "00501"] {
+            assert_eq!(crate::is_real(zipcode).unwrap(), db.is_real(zipcode).unwrap());
+            assert_eq!(crate::matching(zipcode, None).unwrap(), db.matching(zipcode).unwrap());
+        }
+        assert_eq!(crate::similar_to("7742", None).unwrap(), db.similar_to("7742").unwrap());
+        assert!(crate::is_real("7742a").is_err());
+        assert!(crate::similar_to("7742a", None).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+use chrono::{DateTime, FixedOffset, Offset, Utc};
+use chrono_tz::Tz;
+use std::str::FromStr;
+
+use crate::{try_load, Result, ToZip5, Zipcode, ZipcodeDb};
+
+impl Zipcode {
+    /// The IANA time zone of the zipcode, or `None` if its `timezone` field is empty or not a time
+    /// zone name, as for a few records in the database.
+    pub fn tz(&self) -> Option<Tz> {
+        Tz::from_str(&self.timezone).ok()
+    }
+}
+
+impl ZipcodeDb {
+    /// The time zone of the supplied zipcode, as in `crate::timezone`.
+    pub fn timezone<Z: ToZip5>(&self, zipcode: Z) -> Result<Option<Tz>> {
+        Ok(self.get(zipcode)?.and_then(Zipcode::tz))
+    }
+
+    /// The wall clock time in the supplied zipcode, as in `crate::local_time`.
+    pub fn local_time<Z: ToZip5>(&self, zipcode: Z, utc: DateTime<Utc>) -> Result<Option<DateTime<Tz>>> {
+        Ok(self.timezone(zipcode)?.map(|tz| utc.with_timezone(&tz)))
+    }
+
+    /// The offset from UTC in the supplied zipcode, as in `crate::utc_offset`.
+    pub fn utc_offset<Z: ToZip5>(&self, zipcode: Z, at: DateTime<Utc>) -> Result<Option<FixedOffset>> {
+        Ok(self.local_time(zipcode, at)?.map(|local| local.offset().fix()))
+    }
+}
+
+/// The IANA time zone of the supplied zipcode, such as `Tz::America__Chicago` for "77429", or
+/// `None` if the zipcode does not exist or has no recognized time zone.
+///
+/// The supplied zipcode may be in any of the formats accepted by `matching`.
+pub fn timezone<Z: ToZip5>(zipcode: Z) -> Result<Option<Tz>> {
+    try_load()?.timezone(zipcode)
+}
+
+/// The wall clock time in the supplied zipcode at the supplied instant, such as
+/// `local_time("85001", Utc::now())` for the time in Phoenix, or `None` if the zipcode does not
+/// exist or has no recognized time zone.
+///
+/// Offsets come from the tz database bundled with `chrono-tz`, so they account for daylight
+/// saving time, for zones like `America/Phoenix` and `Pacific/Honolulu` that do not observe it,
+/// and for historical changes to either.
+pub fn local_time<Z: ToZip5>(zipcode: Z, utc: DateTime<Utc>) -> Result<Option<DateTime<Tz>>> {
+    try_load()?.local_time(zipcode, utc)
+}
+
+/// The offset from UTC in force in the supplied zipcode at the supplied instant, such as -05:00
+/// for "77429" in July, or `None` if the zipcode does not exist or has no recognized time zone.
+pub fn utc_offset<Z: ToZip5>(zipcode: Z, at: DateTime<Utc>) -> Result<Option<FixedOffset>> {
+    try_load()?.utc_offset(zipcode, at)
+}
+
+#[cfg(all(test, any(feature = "bzip2", feature = "static-data")))]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn should_report_local_time_by_zipcode() {
+        let summer = utc(2024, 7, 4, 16, 30);
+        assert_eq!(timezone("77429").unwrap(), Some(Tz::America__Chicago));
+        assert_eq!(local_time("77429-1145", summer).unwrap().unwrap().to_rfc3339(), "2024-07-04T11:30:00-05:00");
+        assert_eq!(local_time("85001", summer).unwrap().unwrap().to_rfc3339(), "2024-07-04T09:30:00-07:00");
+        assert_eq!(local_time("96813", summer).unwrap().unwrap().format("%Z").to_string(), "HST");
+        assert_eq!(utc_offset("96910", summer).unwrap().unwrap().to_string(), "+10:00");
+
+        // Every time zone in the dataset is recognized, apart from the empty ones and "Napakiak".
+        let unrecognized = crate::iter().unwrap().filter(|z| z.tz().is_none()).count();
+        assert_eq!(unrecognized, 799);
+        assert_eq!(timezone("06463").unwrap(), None);
+        assert!(timezone("0646a").is_err());
+    }
+
+    #[test]
+    fn should_follow_daylight_saving_time() {
+        let hours = |zipcode: &str, at: DateTime<Utc>| utc_offset(zipcode, at).unwrap().unwrap().local_minus_utc() / 3600;
+        let (winter, summer) = (utc(2024, 1, 15, 12, 0), utc(2024, 7, 15, 12, 0));
+        assert_eq!((hours("77429", winter), hours("77429", summer)), (-6, -5));
+        assert_eq!((hours("85001", winter), hours("85001", summer)), (-7, -7));
+        assert_eq!((hours("96813", winter), hours("96813", summer)), (-10, -10));
+        assert_eq!(hours("77429", utc(2024, 3, 10, 7, 59)), -6);
+        assert_eq!(hours("77429", utc(2024, 3, 10, 8, 0)), -5);
+
+        // Historical rules: in 1980 DST began on the last Sunday in April, and most of Indiana
+        // did not observe it until 2006.
+        assert_eq!(hours("77429", utc(1980, 4, 26, 12, 0)), -6);
+        assert_eq!(hours("77429", utc(1980, 4, 27, 8, 0)), -5);
+        assert_eq!(hours("46204", utc(2005, 7, 1, 12, 0)), -5);
+        assert_eq!(hours("46204", utc(2007, 7, 1, 12, 0)), -4);
+    }
+}
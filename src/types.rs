@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{Error, Result};
+
+/// Declares a fieldless enum whose variants map one-to-one onto the strings used in the zipcode
+/// database, with serde, `Display` and `FromStr` all going through that mapping.
+macro_rules! string_enum {
+    ($(#[$meta:meta])* pub enum $name:ident { $($(#[$variant_meta:meta])* $variant:ident => $value:literal,)* }) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+        pub enum $name {
+            $($(#[$variant_meta])* #[serde(rename = $value)] $variant,)*
+        }
+
+        impl $name {
+            /// Every variant, in declaration order.
+            pub const ALL: &'static [$name] = &[$($name::$variant,)*];
+
+            /// The string used for this value in the zipcode database.
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $value,)*
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Error;
+
+            /// Parse the database string for a value, ignoring case and surrounding whitespace.
+            fn from_str(s: &str) -> Result<Self> {
+                let s = s.trim();
+                $name::ALL.iter()
+                    .find(|v| v.as_str().eq_ignore_ascii_case(s))
+                    .copied()
+                    .ok_or_else(|| Error::UnknownVariant { kind: stringify!($name), value: s.to_string() })
+            }
+        }
+    };
+}
+
+// The variants live in their own file so that the build script can read the same mapping.
+include!("types/variants.rs");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_map_database_strings_exactly() {
+        assert_eq!(serde_json::from_str::<ZipcodeType>("\"PO BOX\"").unwrap(), ZipcodeType::PoBox);
+        assert_eq!(serde_json::to_string(&State::ArmedForcesEurope).unwrap(), "\"AE\"");
+        assert_eq!(serde_json::from_str::<WorldRegion>("\"\"").unwrap(), WorldRegion::Unspecified);
+        assert!(serde_json::from_str::<ZipcodeType>("\"PO_BOX\"").is_err());
+        assert!(serde_json::from_str::<State>("\"tx\"").is_err());
+        assert_eq!(State::ALL.len(), 62);
+    }
+
+    #[test]
+    fn should_parse_case_insensitively() {
+        assert_eq!(" tx ".parse::<State>().unwrap(), State::Texas);
+        assert_eq!("po box".parse::<ZipcodeType>().unwrap(), ZipcodeType::PoBox);
+        assert!(matches!("PO_BOX".parse::<ZipcodeType>(), Err(Error::UnknownVariant { kind: "ZipcodeType", .. })));
+    }
+}
@@ -0,0 +1,99 @@
+string_enum! {
+    /// The USPS classification of a zipcode.
+    pub enum ZipcodeType {
+        Standard => "STANDARD",
+        PoBox => "PO BOX",
+        Unique => "UNIQUE",
+        Military => "MILITARY",
+    }
+}
+
+string_enum! {
+    /// A U.S. state, district, territory or freely associated state, or one of the three military
+    /// "states" used for APO/FPO/DPO addresses.
+    pub enum State {
+        Alabama => "AL",
+        Alaska => "AK",
+        Arizona => "AZ",
+        Arkansas => "AR",
+        California => "CA",
+        Colorado => "CO",
+        Connecticut => "CT",
+        Delaware => "DE",
+        Florida => "FL",
+        Georgia => "GA",
+        Hawaii => "HI",
+        Idaho => "ID",
+        Illinois => "IL",
+        Indiana => "IN",
+        Iowa => "IA",
+        Kansas => "KS",
+        Kentucky => "KY",
+        Louisiana => "LA",
+        Maine => "ME",
+        Maryland => "MD",
+        Massachusetts => "MA",
+        Michigan => "MI",
+        Minnesota => "MN",
+        Mississippi => "MS",
+        Missouri => "MO",
+        Montana => "MT",
+        Nebraska => "NE",
+        Nevada => "NV",
+        NewHampshire => "NH",
+        NewJersey => "NJ",
+        NewMexico => "NM",
+        NewYork => "NY",
+        NorthCarolina => "NC",
+        NorthDakota => "ND",
+        Ohio => "OH",
+        Oklahoma => "OK",
+        Oregon => "OR",
+        Pennsylvania => "PA",
+        RhodeIsland => "RI",
+        SouthCarolina => "SC",
+        SouthDakota => "SD",
+        Tennessee => "TN",
+        Texas => "TX",
+        Utah => "UT",
+        Vermont => "VT",
+        Virginia => "VA",
+        Washington => "WA",
+        WestVirginia => "WV",
+        Wisconsin => "WI",
+        Wyoming => "WY",
+        DistrictOfColumbia => "DC",
+        AmericanSamoa => "AS",
+        Guam => "GU",
+        NorthernMarianaIslands => "MP",
+        PuertoRico => "PR",
+        VirginIslands => "VI",
+        FederatedStatesOfMicronesia => "FM",
+        MarshallIslands => "MH",
+        Palau => "PW",
+        /// Armed Forces Americas, excluding Canada.
+        ArmedForcesAmericas => "AA",
+        /// Armed Forces Europe, the Middle East, Africa and Canada.
+        ArmedForcesEurope => "AE",
+        /// Armed Forces Pacific.
+        ArmedForcesPacific => "AP",
+    }
+}
+
+string_enum! {
+    /// The region of the world a zipcode is located in. Military zipcodes abroad carry the region
+    /// of their host country.
+    pub enum WorldRegion {
+        NorthAmerica => "NA",
+        CentralAmerica => "CA",
+        SouthAmerica => "SA",
+        Europe => "EU",
+        MiddleEast => "ME",
+        Africa => "AF",
+        Asia => "AS",
+        Australia => "AU",
+        Worldwide => "WW",
+        /// The database does not record a region for this zipcode.
+        Unspecified => "",
+    }
+}
@@ -0,0 +1,227 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{Error, Result, ZIPCODE_LENGTH};
+
+const PLUS4_LENGTH: usize = 4;
+
+/// A validated 5-digit zipcode, such as `77429`.
+///
+/// Parsing accepts any of the formats "#####", "#####-####", "##### ####" or "#########"; the
+/// ZIP+4 extension, if present, is discarded.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Zip5([u8; ZIPCODE_LENGTH]);
+
+/// A validated ZIP+4 zipcode, such as `77429-1145`.
+///
+/// Parsing accepts any of the formats "#####-####", "##### ####" or "#########", and the value is
+/// always displayed as "#####-####".
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ZipPlus4 {
+    zip5: Zip5,
+    plus4: [u8; PLUS4_LENGTH],
+}
+
+impl Zip5 {
+    /// The five digits of the zipcode.
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.0).expect("zipcode digits are ASCII")
+    }
+}
+
+impl ZipPlus4 {
+    /// The 5-digit zipcode, without the extension.
+    pub fn zip5(&self) -> Zip5 {
+        self.zip5
+    }
+
+    /// The four digits of the ZIP+4 extension.
+    pub fn plus4(&self) -> &str {
+        std::str::from_utf8(&self.plus4).expect("zipcode digits are ASCII")
+    }
+}
+
+impl From<ZipPlus4> for Zip5 {
+    fn from(zipcode: ZipPlus4) -> Self {
+        zipcode.zip5
+    }
+}
+
+impl FromStr for Zip5 {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        parse(s).map(|(zip5, _)| zip5)
+    }
+}
+
+impl FromStr for ZipPlus4 {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match parse(s)? {
+            (zip5, Some(plus4)) => Ok(ZipPlus4 { zip5, plus4 }),
+            (_, None) => Err(Error::MissingPlus4),
+        }
+    }
+}
+
+impl fmt::Display for Zip5 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for ZipPlus4 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.zip5, self.plus4())
+    }
+}
+
+impl fmt::Debug for Zip5 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Zip5({})", self)
+    }
+}
+
+impl fmt::Debug for ZipPlus4 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ZipPlus4({})", self)
+    }
+}
+
+impl Serialize for Zip5 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl Serialize for ZipPlus4 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Zip5 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for ZipPlus4 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Anything that can be resolved to a 5-digit zipcode for a lookup.
+///
+/// Implemented for strings, which are parsed as described on `Zip5`, and for the typed zipcodes.
+pub trait ToZip5 {
+    /// Resolve the value to a 5-digit zipcode.
+    fn to_zip5(&self) -> Result<Zip5>;
+}
+
+impl ToZip5 for str {
+    fn to_zip5(&self) -> Result<Zip5> {
+        self.parse()
+    }
+}
+
+impl ToZip5 for String {
+    fn to_zip5(&self) -> Result<Zip5> {
+        self.parse()
+    }
+}
+
+impl ToZip5 for Zip5 {
+    fn to_zip5(&self) -> Result<Zip5> {
+        Ok(*self)
+    }
+}
+
+impl ToZip5 for ZipPlus4 {
+    fn to_zip5(&self) -> Result<Zip5> {
+        Ok(self.zip5)
+    }
+}
+
+impl<T: ToZip5 + ?Sized> ToZip5 for &T {
+    fn to_zip5(&self) -> Result<Zip5> {
+        (**self).to_zip5()
+    }
+}
+
+fn parse(s: &str) -> Result<(Zip5, Option<[u8; PLUS4_LENGTH]>)> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err(Error::Empty);
+    }
+    let chars = s.chars().collect::<Vec<_>>();
+    let (zip5, plus4) = match chars.len() {
+        ZIPCODE_LENGTH => (&chars[..], None),
+        9 => (&chars[..ZIPCODE_LENGTH], Some(&chars[ZIPCODE_LENGTH..])),
+        10 => {
+            let separator = chars[ZIPCODE_LENGTH];
+            if separator != '-' && separator != ' ' {
+                return Err(Error::InvalidSeparator(separator));
+            }
+            (&chars[..ZIPCODE_LENGTH], Some(&chars[ZIPCODE_LENGTH + 1..]))
+        }
+        _ => return Err(Error::InvalidFormat),
+    };
+    let zip5 = Zip5(digits(zip5)?);
+    let plus4 = plus4.map(digits).transpose()?;
+    Ok((zip5, plus4))
+}
+
+fn digits<const N: usize>(chars: &[char]) -> Result<[u8; N]> {
+    let mut digits = [0; N];
+    for (digit, c) in digits.iter_mut().zip(chars) {
+        if !c.is_ascii_digit() {
+            return Err(Error::InvalidCharacters);
+        }
+        *digit = *c as u8;
+    }
+    Ok(digits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_all_supported_formats() {
+        for s in &["77429-1145", "77429 1145", "774291145", " 77429-1145 "] {
+            let zipcode = s.parse::<ZipPlus4>().unwrap();
+            assert_eq!(zipcode.to_string(), "77429-1145");
+            assert_eq!(zipcode.zip5().as_str(), "77429");
+            assert_eq!(s.parse::<Zip5>().unwrap().as_str(), "77429");
+        }
+        assert_eq!("77429".parse::<Zip5>().unwrap().to_string(), "77429");
+    }
+
+    #[test]
+    fn should_reject_malformed_zipcodes() {
+        assert!(matches!("".parse::<Zip5>(), Err(Error::Empty)));
+        assert!(matches!("12345abc".parse::<Zip5>(), Err(Error::InvalidFormat)));
+        assert!(matches!("123456789012".parse::<Zip5>(), Err(Error::InvalidFormat)));
+        assert!(matches!("0646a".parse::<Zip5>(), Err(Error::InvalidCharacters)));
+        assert!(matches!("12345-678a".parse::<Zip5>(), Err(Error::InvalidCharacters)));
+        assert!(matches!("12345_6789".parse::<Zip5>(), Err(Error::InvalidSeparator('_'))));
+        assert!(matches!("12345".parse::<ZipPlus4>(), Err(Error::MissingPlus4)));
+    }
+
+    #[test]
+    fn should_round_trip_through_serde() {
+        let zipcode = "77429 1145".parse::<ZipPlus4>().unwrap();
+        let json = serde_json::to_string(&zipcode).unwrap();
+        assert_eq!(json, "\"77429-1145\"");
+        assert_eq!(serde_json::from_str::<ZipPlus4>(&json).unwrap(), zipcode);
+        assert_eq!(serde_json::from_str::<Zip5>("\"06903\"").unwrap().as_str(), "06903");
+        assert!(serde_json::from_str::<Zip5>("\"0690\"").is_err());
+    }
+}
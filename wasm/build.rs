@@ -0,0 +1,17 @@
+//! Re-encodes the embedded dataset in the compact format, which needs no bzip2 decoder at runtime
+//! and keeps the `.wasm` bundle small.
+
+use std::env;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use zipcodes::{Format, ZipcodeDb};
+
+fn main() {
+    println!("cargo:rerun-if-changed=../src/zips.json.bz2");
+    let db = ZipcodeDb::embedded().expect("the embedded dataset loads");
+    let path = Path::new(&env::var_os("OUT_DIR").unwrap()).join("zips.bin");
+    let file = BufWriter::new(File::create(path).expect("OUT_DIR is writable"));
+    db.to_writer(file, Format::Compact).expect("the embedded dataset is encodable");
+}
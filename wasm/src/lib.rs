@@ -0,0 +1,112 @@
+//! Browser-side zipcode validation and city/state autofill.
+//!
+//! The dataset is embedded in the crate's compact encoding rather than as bzip2-compressed JSON,
+//! so the `.wasm` bundle carries no decompressor. With the `wasm` feature, `isReal`, `matching`
+//! and `lookupCityState` are exported to JavaScript and return plain objects.
+
+use once_cell::sync::Lazy;
+
+use zipcodes::{CityState, Format, Result, Zipcode, ZipcodeDb};
+
+static ZIPCODE_BYTES_COMPACT: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/zips.bin"));
+
+static DB: Lazy<ZipcodeDb> = Lazy::new(|| {
+    ZipcodeDb::from_reader(ZIPCODE_BYTES_COMPACT, Format::Compact).expect("the build script writes a valid dataset")
+});
+
+/// The embedded database, decoded on first use.
+pub fn db() -> &'static ZipcodeDb {
+    &DB
+}
+
+/// Returns true if the supplied zipcode is a valid zipcode, as in `zipcodes::is_real`.
+pub fn is_real(zipcode: &str) -> Result<bool> {
+    db().is_real(zipcode)
+}
+
+/// Borrow every zipcode matching the supplied zipcode, as in `zipcodes::matching_ref`.
+pub fn matching(zipcode: &str) -> Result<&'static [Zipcode]> {
+    db().matching_ref(zipcode)
+}
+
+/// The city and state to autofill for the supplied zipcode, as in `zipcodes::city_state`.
+pub fn lookup_city_state(zipcode: &str) -> Result<Option<CityState<'static>>> {
+    db().city_state(zipcode)
+}
+
+#[cfg(feature = "wasm")]
+mod bindings {
+    use serde::Serialize;
+    use wasm_bindgen::prelude::*;
+
+    /// Convert a value into a plain JavaScript object, with `None` as `null`.
+    fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsError> {
+        Ok(value.serialize(&serde_wasm_bindgen::Serializer::json_compatible())?)
+    }
+
+    /// Whether the supplied zipcode exists. Throws if it is malformed.
+    #[wasm_bindgen(js_name = isReal)]
+    pub fn is_real(zipcode: &str) -> Result<bool, JsError> {
+        Ok(super::is_real(zipcode)?)
+    }
+
+    /// The records matching the supplied zipcode, as an array of objects. Throws if it is
+    /// malformed.
+    #[wasm_bindgen]
+    pub fn matching(zipcode: &str) -> Result<JsValue, JsError> {
+        to_js(&super::matching(zipcode)?)
+    }
+
+    /// A `{ city, state, acceptable_cities, unacceptable_cities }` object for the supplied
+    /// zipcode, or `null` if it does not exist. Throws if it is malformed.
+    #[wasm_bindgen(js_name = lookupCityState)]
+    pub fn lookup_city_state(zipcode: &str) -> Result<JsValue, JsError> {
+        to_js(&super::lookup_city_state(zipcode)?)
+    }
+
+    // Run under Node.js with `wasm-pack test --node`.
+    #[cfg(all(test, target_arch = "wasm32"))]
+    mod tests {
+        use super::*;
+        use serde_json::{json, Value};
+        use wasm_bindgen_test::wasm_bindgen_test;
+
+        fn from_js(value: JsValue) -> Value {
+            serde_wasm_bindgen::from_value(value).unwrap()
+        }
+
+        #[wasm_bindgen_test]
+        fn should_export_plain_objects() {
+            assert!(is_real("06469").unwrap());
+            assert!(!is_real("06463").unwrap());
+            assert!(is_real("0646a").is_err());
+
+            let cypress = from_js(matching("77429-1145").unwrap());
+            assert_eq!(cypress[0]["county"], "Harris County");
+            assert_eq!(cypress[0]["state"], "TX");
+            assert_eq!(from_js(lookup_city_state("06475").unwrap()), json!({
+                "city": "Old Saybrook",
+                "state": "CT",
+                "acceptable_cities": [],
+                "unacceptable_cities": ["Fenwick"],
+            }));
+            assert!(lookup_city_state("06463").unwrap().is_null());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_decode_the_compact_dataset() {
+        assert_eq!(db().len(), 42_724);
+        assert!(is_real("06469").unwrap());
+        assert_eq!(matching("77429-1145").unwrap()[0].county, "Harris County");
+        let old_saybrook = lookup_city_state("06475").unwrap().unwrap();
+        assert_eq!((old_saybrook.city, old_saybrook.state.as_str()), ("Old Saybrook", "CT"));
+        assert_eq!(lookup_city_state("06463").unwrap(), None);
+        assert!(lookup_city_state("0646a").is_err());
+    }
+}